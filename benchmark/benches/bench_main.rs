@@ -1,7 +1,20 @@
 use criterion::criterion_main;
 
-mod sync_naive_lru;
+mod fused_lru;
+mod iteration;
+mod matrix;
+mod profiler;
+mod ratio;
+mod small_lru;
+mod ttl_cache;
+mod ycsb;
 
 criterion_main! {
-    sync_naive_lru::sync_naive_lru_benches,
+    fused_lru::fused_lru_benches,
+    iteration::iteration_benches,
+    matrix::matrix_benches,
+    ratio::ratio_benches,
+    small_lru::small_lru_benches,
+    ttl_cache::ttl_cache_benches,
+    ycsb::ycsb_benches,
 }
@@ -0,0 +1,148 @@
+//! YCSB workloads run against popular cache crates, next to `SyncNaiveLru` as the baseline.
+
+use benchmark::ycsb::{execute, OperationGenerator, Workload};
+use common::Cache;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::num::NonZeroUsize;
+use sync_naive_lru::SyncNaiveLru;
+
+mod profiler;
+
+const RECORD_COUNT: u64 = 100_000;
+const CAPACITY: u64 = RECORD_COUNT / 10;
+const OPERATION_COUNT: usize = 100_000;
+
+/// Newtypes implementing `Cache`, which the orphan rule forbids for the external types
+/// themselves.
+struct Lru(lru::LruCache<u64, u64>);
+
+impl Cache<u64, u64> for Lru {
+    fn insert(&mut self, key: u64, value: u64) {
+        self.0.put(key, value);
+    }
+
+    fn get(&mut self, key: &u64) -> Option<u64> {
+        self.0.get(key).copied()
+    }
+
+    fn get_quiet(&self, key: &u64) -> Option<u64> {
+        self.0.peek(key).copied()
+    }
+
+    fn remove(&mut self, key: &u64) -> Option<u64> {
+        self.0.pop(key)
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+struct Moka(moka::sync::Cache<u64, u64>);
+
+impl Cache<u64, u64> for Moka {
+    fn insert(&mut self, key: u64, value: u64) {
+        self.0.insert(key, value);
+    }
+
+    fn get(&mut self, key: &u64) -> Option<u64> {
+        self.0.get(key)
+    }
+
+    /// Moka has no lookup leaving its policy alone, so this counts as an access too.
+    fn get_quiet(&self, key: &u64) -> Option<u64> {
+        self.0.get(key)
+    }
+
+    fn remove(&mut self, key: &u64) -> Option<u64> {
+        self.0.remove(key)
+    }
+
+    fn clear(&mut self) {
+        self.0.invalidate_all();
+    }
+
+    /// Approximate until moka has run its pending maintenance.
+    fn len(&self) -> usize {
+        self.0.entry_count() as usize
+    }
+}
+
+struct QuickCache(quick_cache::unsync::Cache<u64, u64>);
+
+impl Cache<u64, u64> for QuickCache {
+    fn insert(&mut self, key: u64, value: u64) {
+        self.0.insert(key, value);
+    }
+
+    fn get(&mut self, key: &u64) -> Option<u64> {
+        self.0.get(key).copied()
+    }
+
+    fn get_quiet(&self, key: &u64) -> Option<u64> {
+        self.0.peek(key).copied()
+    }
+
+    fn remove(&mut self, key: &u64) -> Option<u64> {
+        self.0.remove(key).map(|(_, value)| value)
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+fn bench_cache<C: Cache<u64, u64>>(
+    c: &mut Criterion,
+    name: &str,
+    mut make: impl FnMut(usize) -> C,
+) {
+    let mut group = c.benchmark_group(format!("external/{}", name));
+    for workload in Workload::ALL {
+        let operations = OperationGenerator::new(workload, RECORD_COUNT, 0)
+            .take(OPERATION_COUNT)
+            .collect::<Vec<_>>();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(workload.name()),
+            &operations,
+            |b, operations| {
+                let mut cache = make(CAPACITY as usize);
+                (0..CAPACITY).for_each(|key| cache.insert(key, key));
+                let mut i = 0;
+                b.iter(|| {
+                    black_box(execute(&mut cache, operations[i]));
+                    i = (i + 1) % operations.len();
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_cache(c, "sync_naive_lru", SyncNaiveLru::new);
+    bench_cache(c, "lru", |capacity| {
+        Lru(lru::LruCache::new(NonZeroUsize::new(capacity).unwrap()))
+    });
+    bench_cache(c, "moka", |capacity| {
+        Moka(moka::sync::Cache::new(capacity as u64))
+    });
+    bench_cache(c, "quick_cache", |capacity| {
+        QuickCache(quick_cache::unsync::Cache::new(capacity))
+    });
+}
+
+criterion_group! {
+    name = external_benches;
+    config = profiler::config();
+    targets = criterion_benchmark
+}
+criterion_main!(external_benches);
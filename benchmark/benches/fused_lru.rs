@@ -0,0 +1,78 @@
+use common::Cache;
+use criterion::{black_box, criterion_group, BenchmarkId, Criterion};
+use fused_lru::FusedLru;
+use sync_naive_lru::SyncNaiveLru;
+
+const CAPACITIES: [u64; 3] = [100, 1_000, 10_000];
+
+fn filled<C: Cache<u64, u64>>(mut cache: C, capacity: u64) -> C {
+    (0..capacity).for_each(|i| cache.insert(i, i));
+    cache
+}
+
+/// Compare the fused hash table against hash map + separate linked list.
+fn fused_vs_naive(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fused_lru_get");
+    for capacity in CAPACITIES {
+        group.bench_with_input(
+            BenchmarkId::new("FusedLru", capacity),
+            &capacity,
+            |b, &capacity| {
+                let mut lru = filled(FusedLru::new(capacity as usize), capacity);
+                let mut i = 0;
+                b.iter(|| {
+                    i = (i + 1) % capacity;
+                    black_box(lru.get(&i));
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("SyncNaiveLru", capacity),
+            &capacity,
+            |b, &capacity| {
+                let mut lru = filled(SyncNaiveLru::new(capacity as usize), capacity);
+                let mut i = 0;
+                b.iter(|| {
+                    i = (i + 1) % capacity;
+                    black_box(lru.get(&i));
+                });
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("fused_lru_insert_with_eviction");
+    for capacity in CAPACITIES {
+        group.bench_with_input(
+            BenchmarkId::new("FusedLru", capacity),
+            &capacity,
+            |b, &capacity| {
+                let mut lru = filled(FusedLru::new(capacity as usize), capacity);
+                let mut i = capacity;
+                b.iter(|| {
+                    i += 1;
+                    lru.insert(black_box(i), i);
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("SyncNaiveLru", capacity),
+            &capacity,
+            |b, &capacity| {
+                let mut lru = filled(SyncNaiveLru::new(capacity as usize), capacity);
+                let mut i = capacity;
+                b.iter(|| {
+                    i += 1;
+                    lru.insert(black_box(i), i);
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = fused_lru_benches;
+    config = crate::profiler::config();
+    targets = fused_vs_naive
+}
@@ -0,0 +1,78 @@
+use common::Cache;
+use criterion::{black_box, criterion_group, BatchSize, BenchmarkId, Criterion};
+use fused_lru::FusedLru;
+use sync_naive_lru::{ArenaLru, SyncNaiveLru};
+
+const CAPACITIES: [usize; 3] = [100, 1_000, 10_000];
+
+/// Cache full of string keys, so that cloning instead of moving them while iterating shows.
+fn filled<C: Cache<String, u64>>(mut cache: C, capacity: usize) -> C {
+    (0..capacity as u64).for_each(|i| cache.insert(format!("key{}", i), i));
+    cache
+}
+
+/// Consume a full cache by `IntoIterator`, including freeing its nodes.
+fn bench_into_iter<C>(c: &mut Criterion, name: &str, make: impl Fn(usize) -> C)
+where
+    C: Cache<String, u64> + IntoIterator<Item = (String, u64)>,
+{
+    let mut group = c.benchmark_group("iteration_into_iter");
+    for capacity in CAPACITIES {
+        group.bench_with_input(
+            BenchmarkId::new(name, capacity),
+            &capacity,
+            |b, &capacity| {
+                b.iter_batched(
+                    || filled(make(capacity), capacity),
+                    |cache| cache.into_iter().for_each(|item| drop(black_box(item))),
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Fill an empty cache, which is dominated by allocating nodes and growing the map.
+fn bench_fill<C: Cache<String, u64>>(c: &mut Criterion, name: &str, make: impl Fn(usize) -> C) {
+    let mut group = c.benchmark_group("iteration_fill");
+    for capacity in CAPACITIES {
+        group.bench_with_input(
+            BenchmarkId::new(name, capacity),
+            &capacity,
+            |b, &capacity| {
+                b.iter_batched(
+                    || {
+                        (0..capacity as u64)
+                            .map(|i| (format!("key{}", i), i))
+                            .collect::<Vec<_>>()
+                    },
+                    |items| {
+                        let mut cache = make(capacity);
+                        items
+                            .into_iter()
+                            .for_each(|(key, value)| cache.insert(key, value));
+                        cache
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+fn iteration(c: &mut Criterion) {
+    bench_into_iter(c, "SyncNaiveLru", SyncNaiveLru::new);
+    bench_into_iter(c, "ArenaLru", ArenaLru::new);
+    bench_into_iter(c, "FusedLru", FusedLru::new);
+    bench_fill(c, "SyncNaiveLru", SyncNaiveLru::new);
+    bench_fill(c, "ArenaLru", ArenaLru::new);
+    bench_fill(c, "FusedLru", FusedLru::new);
+}
+
+criterion_group! {
+    name = iteration_benches;
+    config = crate::profiler::config();
+    targets = iteration
+}
@@ -0,0 +1,238 @@
+use benchmark::workload::{Distribution, KeyGenerator};
+use common::Cache;
+use criterion::{black_box, criterion_group, BenchmarkId, Criterion};
+use fused_lru::FusedLru;
+use std::hash::Hash;
+use sync_naive_lru::{ArenaLru, SyncNaiveLru};
+
+const CAPACITIES: [u64; 5] = [100, 1_000, 10_000, 100_000, 1_000_000];
+
+/// Number of keys never inserted, looked up by `get_miss`.
+const MISS_KEYS: u64 = 1024;
+
+/// Number of lookups cycled through by `mixed_get_or_insert`.
+const MIXED_KEYS: usize = 1 << 14;
+
+trait BenchKey: Hash + Eq + Clone {
+    const NAME: &'static str;
+    /// Largest capacity benchmarked, to bound memory taken by key pools.
+    const MAX_CAPACITY: u64;
+
+    fn from_index(index: u64) -> Self;
+}
+
+impl BenchKey for u64 {
+    const NAME: &'static str = "u64";
+    const MAX_CAPACITY: u64 = u64::MAX;
+
+    fn from_index(index: u64) -> Self {
+        index
+    }
+}
+
+/// String short enough to be typical of identifiers.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SmallString(String);
+
+impl BenchKey for SmallString {
+    const NAME: &'static str = "small_string";
+    const MAX_CAPACITY: u64 = u64::MAX;
+
+    fn from_index(index: u64) -> Self {
+        Self(format!("key:{}", index))
+    }
+}
+
+/// 256-byte string, typical of URLs and paths.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct LargeString(String);
+
+impl BenchKey for LargeString {
+    const NAME: &'static str = "large_string";
+    const MAX_CAPACITY: u64 = 100_000;
+
+    fn from_index(index: u64) -> Self {
+        Self(format!("{:/>256}", index))
+    }
+}
+
+trait Policy {
+    const NAME: &'static str;
+    type Cache<K: BenchKey>: Cache<K, u64>;
+
+    fn new<K: BenchKey>(capacity: usize) -> Self::Cache<K>;
+}
+
+struct Naive;
+
+impl Policy for Naive {
+    const NAME: &'static str = "sync_naive_lru";
+    type Cache<K: BenchKey> = SyncNaiveLru<K, u64>;
+
+    fn new<K: BenchKey>(capacity: usize) -> Self::Cache<K> {
+        SyncNaiveLru::new(capacity)
+    }
+}
+
+struct Arena;
+
+impl Policy for Arena {
+    const NAME: &'static str = "arena_lru";
+    type Cache<K: BenchKey> = ArenaLru<K, u64>;
+
+    fn new<K: BenchKey>(capacity: usize) -> Self::Cache<K> {
+        ArenaLru::new(capacity)
+    }
+}
+
+struct Fused;
+
+impl Policy for Fused {
+    const NAME: &'static str = "fused_lru";
+    type Cache<K: BenchKey> = FusedLru<K, u64>;
+
+    fn new<K: BenchKey>(capacity: usize) -> Self::Cache<K> {
+        FusedLru::new(capacity)
+    }
+}
+
+/// Keys prepared before measurement, so that building keys is not measured.
+struct Keys<K> {
+    capacity: u64,
+    /// `capacity + 1` keys. The first `capacity` ones are resident after filling a cache, and
+    /// inserting them cyclically from there always evicts.
+    pool: Vec<K>,
+    misses: Vec<K>,
+    /// Keys drawn uniformly from twice as many as fit, so that about half of the lookups hit.
+    mixed: Vec<K>,
+}
+
+impl<K: BenchKey> Keys<K> {
+    fn new(capacity: u64) -> Self {
+        Self {
+            capacity,
+            pool: (0..=capacity).map(K::from_index).collect(),
+            misses: (0..MISS_KEYS)
+                .map(|i| K::from_index(capacity + 1 + i))
+                .collect(),
+            mixed: KeyGenerator::new(Distribution::Uniform { keys: capacity * 2 }, 0)
+                .take(MIXED_KEYS)
+                .map(K::from_index)
+                .collect(),
+        }
+    }
+
+    fn filled<P: Policy>(&self) -> P::Cache<K> {
+        let mut cache = P::new(self.capacity as usize);
+        self.pool[..self.capacity as usize]
+            .iter()
+            .for_each(|key| cache.insert(key.clone(), 0));
+        cache
+    }
+}
+
+fn bench_policy<P: Policy, K: BenchKey>(c: &mut Criterion, keys_by_capacity: &[Keys<K>]) {
+    let name = |op| format!("matrix/{}/{}/{}", P::NAME, op, K::NAME);
+
+    let mut group = c.benchmark_group(name("get_hit"));
+    for keys in keys_by_capacity {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(keys.capacity),
+            keys,
+            |b, keys| {
+                let mut cache = keys.filled::<P>();
+                let resident = &keys.pool[..keys.capacity as usize];
+                let mut i = 0;
+                b.iter(|| {
+                    black_box(cache.get(&resident[i]));
+                    i = (i + 1) % resident.len();
+                });
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group(name("get_miss"));
+    for keys in keys_by_capacity {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(keys.capacity),
+            keys,
+            |b, keys| {
+                let mut cache = keys.filled::<P>();
+                let mut i = 0;
+                b.iter(|| {
+                    black_box(cache.get(&keys.misses[i]));
+                    i = (i + 1) % keys.misses.len();
+                });
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group(name("insert_with_eviction"));
+    for keys in keys_by_capacity {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(keys.capacity),
+            keys,
+            |b, keys| {
+                let mut cache = keys.filled::<P>();
+                let mut i = keys.capacity as usize;
+                b.iter_batched(
+                    || {
+                        let key = keys.pool[i].clone();
+                        i = (i + 1) % keys.pool.len();
+                        key
+                    },
+                    |key| cache.insert(key, 0),
+                    criterion::BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+
+    // Look-aside use in steady state: a miss inserts its key, cloning it as a caller owning only
+    // a borrowed key would have to.
+    let mut group = c.benchmark_group(name("mixed_get_or_insert"));
+    for keys in keys_by_capacity {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(keys.capacity),
+            keys,
+            |b, keys| {
+                let mut cache = keys.filled::<P>();
+                let mut i = 0;
+                b.iter(|| {
+                    let key = &keys.mixed[i];
+                    i = (i + 1) % keys.mixed.len();
+                    if cache.get(black_box(key)).is_none() {
+                        cache.insert(key.clone(), 0);
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_key<K: BenchKey>(c: &mut Criterion) {
+    let keys_by_capacity = CAPACITIES
+        .iter()
+        .filter(|&&capacity| capacity <= K::MAX_CAPACITY)
+        .map(|&capacity| Keys::<K>::new(capacity))
+        .collect::<Vec<_>>();
+    bench_policy::<Naive, K>(c, &keys_by_capacity);
+    bench_policy::<Arena, K>(c, &keys_by_capacity);
+    bench_policy::<Fused, K>(c, &keys_by_capacity);
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_key::<u64>(c);
+    bench_key::<SmallString>(c);
+    bench_key::<LargeString>(c);
+}
+
+criterion_group! {
+    name = matrix_benches;
+    config = crate::profiler::config();
+    targets = criterion_benchmark
+}
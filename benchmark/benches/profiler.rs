@@ -0,0 +1,60 @@
+use criterion::Criterion;
+
+/// Criterion configuration shared by every benchmark group.
+/// With `profiling` feature, running benchmarks with `--profile-time <seconds>` writes
+/// `flamegraph.svg` into `target/criterion/<benchmark>/profile`.
+pub fn config() -> Criterion {
+    #[cfg(feature = "profiling")]
+    {
+        Criterion::default().with_profiler(flamegraph::FlamegraphProfiler::new(997))
+    }
+    #[cfg(not(feature = "profiling"))]
+    {
+        Criterion::default()
+    }
+}
+
+#[cfg(feature = "profiling")]
+mod flamegraph {
+    use criterion::profiler::Profiler;
+    use pprof::ProfilerGuard;
+    use std::{
+        fs::{self, File},
+        path::Path,
+    };
+
+    /// Sample the benchmark with pprof and render the samples as a flamegraph.
+    pub struct FlamegraphProfiler {
+        /// Samples per second.
+        frequency: i32,
+        guard: Option<ProfilerGuard<'static>>,
+    }
+
+    impl FlamegraphProfiler {
+        pub fn new(frequency: i32) -> Self {
+            Self {
+                frequency,
+                guard: None,
+            }
+        }
+    }
+
+    impl Profiler for FlamegraphProfiler {
+        fn start_profiling(&mut self, _benchmark_id: &str, _benchmark_dir: &Path) {
+            self.guard = Some(ProfilerGuard::new(self.frequency).expect("Failed to start pprof"));
+        }
+
+        fn stop_profiling(&mut self, _benchmark_id: &str, benchmark_dir: &Path) {
+            let guard = self.guard.take().expect("Profiling must have started");
+            fs::create_dir_all(benchmark_dir).expect("Failed to create benchmark directory");
+            let file = File::create(benchmark_dir.join("flamegraph.svg"))
+                .expect("Failed to create flamegraph file");
+            guard
+                .report()
+                .build()
+                .expect("Failed to build pprof report")
+                .flamegraph(file)
+                .expect("Failed to write flamegraph");
+        }
+    }
+}
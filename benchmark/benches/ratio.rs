@@ -0,0 +1,82 @@
+use benchmark::workload::{Distribution, KeyGenerator};
+use common::{rng::Rng, Cache};
+use criterion::{black_box, criterion_group, BenchmarkId, Criterion, Throughput};
+use fused_lru::FusedLru;
+use sync_naive_lru::{ArenaLru, SyncNaiveLru};
+
+/// Percentage of gets against inserts.
+const GET_PERCENTAGES: [u64; 3] = [99, 90, 50];
+const CAPACITIES: [u64; 3] = [1_000, 10_000, 100_000];
+const OPERATION_COUNT: usize = 100_000;
+
+#[derive(Clone, Copy)]
+enum Operation {
+    Get(u64),
+    Insert(u64),
+}
+
+/// Operations over a key space 10 times as large as `capacity`, drawn from a zipfian distribution.
+fn operations(get_percentage: u64, capacity: u64) -> Vec<Operation> {
+    let distribution = Distribution::Zipf {
+        keys: capacity * 10,
+        skew: 0.99,
+    };
+    let mut rng = Rng::new(0);
+    KeyGenerator::new(distribution, 1)
+        .take(OPERATION_COUNT)
+        .map(|key| {
+            if rng.below(100) < get_percentage {
+                Operation::Get(key)
+            } else {
+                Operation::Insert(key)
+            }
+        })
+        .collect()
+}
+
+fn bench_policy<C: Cache<u64, u64>>(
+    c: &mut Criterion,
+    policy: &str,
+    mut make: impl FnMut(usize) -> C,
+) {
+    let mut group = c.benchmark_group(format!("ratio/{}", policy));
+    // One operation per iteration, so that Criterion reports operations per second.
+    group.throughput(Throughput::Elements(1));
+    for capacity in CAPACITIES {
+        for get_percentage in GET_PERCENTAGES {
+            let parameter = format!("{}:{}/{}", get_percentage, 100 - get_percentage, capacity);
+            let operations = operations(get_percentage, capacity);
+            group.bench_with_input(
+                BenchmarkId::from_parameter(parameter),
+                &operations,
+                |b, operations| {
+                    let mut cache = make(capacity as usize);
+                    (0..capacity).for_each(|key| cache.insert(key, key));
+                    let mut i = 0;
+                    b.iter(|| {
+                        match operations[i] {
+                            Operation::Get(key) => {
+                                black_box(cache.get(&key));
+                            }
+                            Operation::Insert(key) => cache.insert(key, key),
+                        }
+                        i = (i + 1) % operations.len();
+                    });
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_policy(c, "sync_naive_lru", SyncNaiveLru::new);
+    bench_policy(c, "arena_lru", ArenaLru::new);
+    bench_policy(c, "fused_lru", FusedLru::new);
+}
+
+criterion_group! {
+    name = ratio_benches;
+    config = crate::profiler::config();
+    targets = criterion_benchmark
+}
@@ -0,0 +1,68 @@
+use benchmark::workload::{Distribution, KeyGenerator};
+use common::Cache;
+use criterion::{black_box, criterion_group, BenchmarkId, Criterion};
+use small_lru::SmallLru;
+use sync_naive_lru::SyncNaiveLru;
+
+const CAPACITIES: [u64; 8] = [2, 4, 8, 16, 32, 64, 128, 256];
+/// Number of keys drawn before measurement and looked up cyclically.
+const KEYS: usize = 1 << 12;
+
+fn filled<C: Cache<u64, u64>>(mut cache: C, capacity: u64) -> C {
+    (0..capacity).for_each(|i| cache.insert(i, i));
+    cache
+}
+
+/// `KEYS` keys drawn uniformly from `0..keys`.
+fn uniform(keys: u64) -> Vec<u64> {
+    KeyGenerator::new(Distribution::Uniform { keys }, 0)
+        .take(KEYS)
+        .collect()
+}
+
+/// Look `keys` up cyclically, inserting the keys which miss.
+fn get_or_insert<C: Cache<u64, u64>>(cache: &mut C, keys: &[u64], i: &mut usize) {
+    let key = keys[*i];
+    if black_box(cache.get(&key)).is_none() {
+        cache.insert(key, key);
+    }
+    *i = (*i + 1) % keys.len();
+}
+
+fn bench_workload(c: &mut Criterion, name: &str, keys: impl Fn(u64) -> Vec<u64>) {
+    let mut group = c.benchmark_group(format!("small_lru_crossover/{}", name));
+    for capacity in CAPACITIES {
+        let keys = keys(capacity);
+        group.bench_with_input(BenchmarkId::new("SmallLru", capacity), &keys, |b, keys| {
+            let mut lru = filled(SmallLru::new(capacity as usize), capacity);
+            let mut i = 0;
+            b.iter(|| get_or_insert(&mut lru, keys, &mut i));
+        });
+        group.bench_with_input(
+            BenchmarkId::new("SyncNaiveLru", capacity),
+            &keys,
+            |b, keys| {
+                let mut lru = filled(SyncNaiveLru::new(capacity as usize), capacity);
+                let mut i = 0;
+                b.iter(|| get_or_insert(&mut lru, keys, &mut i));
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Compare `SmallLru` against `SyncNaiveLru` at small capacities to find the crossover point.
+/// Keys are random, so that a hit scans on average half of the array instead of finding the
+/// least recent element at the front as cyclic lookups do.
+fn crossover(c: &mut Criterion) {
+    // Every key is resident.
+    bench_workload(c, "uniform_hit", uniform);
+    // Three lookups in four miss, scan the whole array and evict.
+    bench_workload(c, "miss_heavy", |capacity| uniform(capacity * 4));
+}
+
+criterion_group! {
+    name = small_lru_benches;
+    config = crate::profiler::config();
+    targets = crossover
+}
@@ -0,0 +1,58 @@
+use common::Cache;
+use criterion::{black_box, criterion_group, BenchmarkId, Criterion};
+use std::time::Duration;
+use ttl_cache::{ManualClock, TtlCache};
+
+const CAPACITIES: [u64; 3] = [100, 1_000, 10_000];
+
+/// Cost of expiry alone: every insert makes the oldest element expire, so the cache stays at
+/// `capacity` elements and each insert purges one.
+fn expiry(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ttl_cache_insert_with_expiry");
+    for capacity in CAPACITIES {
+        group.bench_with_input(
+            BenchmarkId::new("TtlCache", capacity),
+            &capacity,
+            |b, &capacity| {
+                let clock = ManualClock::new();
+                let mut cache =
+                    TtlCache::with_clock(Duration::from_millis(capacity), clock.clone());
+                (0..capacity).for_each(|i| {
+                    cache.insert(i, i);
+                    clock.advance(Duration::from_millis(1));
+                });
+                let mut i = capacity;
+                b.iter(|| {
+                    i += 1;
+                    cache.insert(black_box(i), i);
+                    clock.advance(Duration::from_millis(1));
+                });
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("ttl_cache_get");
+    for capacity in CAPACITIES {
+        group.bench_with_input(
+            BenchmarkId::new("TtlCache", capacity),
+            &capacity,
+            |b, &capacity| {
+                let mut cache = TtlCache::new(Duration::from_secs(3600));
+                (0..capacity).for_each(|i| cache.insert(i, i));
+                let mut i = 0;
+                b.iter(|| {
+                    i = (i + 1) % capacity;
+                    black_box(cache.get(&i));
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = ttl_cache_benches;
+    config = crate::profiler::config();
+    targets = expiry
+}
@@ -0,0 +1,49 @@
+use benchmark::ycsb::{self, OperationGenerator, Workload};
+use common::Cache;
+use criterion::{black_box, criterion_group, BenchmarkId, Criterion};
+use fused_lru::FusedLru;
+use sync_naive_lru::{ArenaLru, SyncNaiveLru};
+
+const RECORD_COUNT: u64 = 100_000;
+const CAPACITY: u64 = RECORD_COUNT / 10;
+const OPERATION_COUNT: usize = 100_000;
+
+fn bench_policy<C: Cache<u64, u64>>(
+    c: &mut Criterion,
+    policy: &str,
+    mut make: impl FnMut(usize) -> C,
+) {
+    let mut group = c.benchmark_group(format!("ycsb/{}", policy));
+    for workload in Workload::ALL {
+        let operations = OperationGenerator::new(workload, RECORD_COUNT, 0)
+            .take(OPERATION_COUNT)
+            .collect::<Vec<_>>();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(workload.name()),
+            &operations,
+            |b, operations| {
+                let mut cache = make(CAPACITY as usize);
+                (0..CAPACITY).for_each(|key| cache.insert(key, key));
+                let mut i = 0;
+                b.iter(|| {
+                    black_box(ycsb::execute(&mut cache, operations[i]));
+                    i = (i + 1) % operations.len();
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_policy(c, "sync_naive_lru", SyncNaiveLru::new);
+    bench_policy(c, "arena_lru", ArenaLru::new);
+    bench_policy(c, "fused_lru", FusedLru::new);
+    // `SmallLru` is left out; a linear scan over `CAPACITY` entries is not what it is built for.
+}
+
+criterion_group! {
+    name = ycsb_benches;
+    config = crate::profiler::config();
+    targets = criterion_benchmark
+}
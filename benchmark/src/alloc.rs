@@ -0,0 +1,100 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Global allocator delegating to `System` while counting allocated bytes.
+/// Install it with `#[global_allocator]` in a binary to measure memory footprint of caches.
+pub struct CountingAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    total: AtomicUsize,
+    allocations: AtomicUsize,
+}
+
+/// Counters of `CountingAllocator` at some point.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Bytes currently allocated.
+    pub current: usize,
+    /// Largest value of `current` since the last reset.
+    pub peak: usize,
+    /// Bytes allocated in total, including freed ones.
+    pub total: usize,
+    pub allocations: usize,
+}
+
+impl CountingAllocator {
+    pub const fn new() -> Self {
+        Self {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            total: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            current: self.current.load(Ordering::SeqCst),
+            peak: self.peak.load(Ordering::SeqCst),
+            total: self.total.load(Ordering::SeqCst),
+            allocations: self.allocations.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Start tracking peak from the current usage.
+    pub fn reset_peak(&self) {
+        self.peak
+            .store(self.current.load(Ordering::SeqCst), Ordering::SeqCst);
+    }
+
+    fn add(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::SeqCst) + size;
+        self.peak.fetch_max(current, Ordering::SeqCst);
+        self.total.fetch_add(size, Ordering::SeqCst);
+        self.allocations.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn sub(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::SeqCst);
+    }
+}
+
+impl Default for CountingAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.sub(layout.size());
+            self.add(new_size);
+        }
+        new_ptr
+    }
+}
@@ -0,0 +1,133 @@
+//! Print hit ratio of every policy on generated workloads and on the scan-resistance scenario, or
+//! on a trace file given as an argument. Results are also written to `compare.csv` and
+//! `compare.json` in the results directory, or under `--output <dir>`.
+//!
+//! Usage: `compare [--seed <n>] [--output <dir>] [<trace file>]`
+
+use benchmark::{
+    results::{self, Record},
+    scan::{self, ScanScenario},
+    simulator::{self, read_trace},
+    workload::{Distribution, KeyGenerator},
+};
+use std::{env, path::PathBuf, process};
+
+const CAPACITIES: [usize; 4] = [16, 100, 1_000, 10_000];
+const KEY_COUNT: u64 = 100_000;
+const TRACE_LENGTH: usize = 1_000_000;
+/// Capacities between the hot set and the hot set plus a scan of the default scenario.
+const SCAN_CAPACITIES: [usize; 3] = [1_500, 5_000, 10_000];
+
+/// Parse `[--seed <n>] [--output <dir>] [<trace file>]`.
+fn parse_args() -> Result<(u64, Option<PathBuf>, Option<String>), String> {
+    let mut seed = 0;
+    let mut output = None;
+    let mut path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                seed = args
+                    .next()
+                    .and_then(|seed| seed.parse().ok())
+                    .ok_or("--seed requires an integer")?;
+            }
+            "--output" => {
+                output = Some(PathBuf::from(
+                    args.next().ok_or("--output requires a value")?,
+                ));
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => {
+                return Err(
+                    "Usage: compare [--seed <n>] [--output <dir>] [<trace file>]".to_string(),
+                )
+            }
+        }
+    }
+    Ok((seed, output, path))
+}
+
+fn main() {
+    let (seed, output, path) = parse_args().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+    let traces = match path.clone() {
+        Some(path) => match read_trace(&path) {
+            Ok(trace) => vec![(path, trace)],
+            Err(err) => {
+                eprintln!("Failed to read {}: {}", path, err);
+                process::exit(1);
+            }
+        },
+        None => [
+            Distribution::Uniform { keys: KEY_COUNT },
+            Distribution::Zipf {
+                keys: KEY_COUNT,
+                skew: 0.99,
+            },
+            Distribution::Hotspot {
+                keys: KEY_COUNT,
+                hot_fraction: 0.01,
+                hot_probability: 0.9,
+            },
+            Distribution::Sequential { keys: KEY_COUNT },
+        ]
+        .into_iter()
+        .map(|distribution| {
+            let name = format!("{:?}", distribution);
+            let trace = KeyGenerator::new(distribution, seed)
+                .take(TRACE_LENGTH)
+                .collect();
+            (name, trace)
+        })
+        .collect(),
+    };
+
+    let commit = results::current_commit();
+    let mut records = Vec::new();
+    for (name, trace) in traces {
+        let rows = simulator::compare(&trace, &CAPACITIES, seed);
+        println!("{}", name);
+        print!("{}", simulator::format_table(&rows));
+        println!();
+        records.extend(rows.iter().map(|row| Record {
+            commit: commit.clone(),
+            policy: row.policy.to_string(),
+            capacity: row.capacity,
+            workload: name.clone(),
+            hit_ratio: Some(row.stats.hit_ratio()),
+            ops_per_sec: Some(row.ops_per_sec()),
+            bytes_per_entry: None,
+        }));
+    }
+
+    if path.is_none() {
+        let scenario = ScanScenario {
+            seed,
+            ..Default::default()
+        };
+        let rows = scan::compare(&scenario, &SCAN_CAPACITIES);
+        println!("{:?}", scenario);
+        print!("{}", scan::format_table(&rows));
+        println!();
+        records.extend(rows.iter().map(|row| Record {
+            commit: commit.clone(),
+            policy: row.policy.to_string(),
+            capacity: row.capacity,
+            workload: "scan_resistance_hot_set".to_string(),
+            hit_ratio: Some(row.hot.hit_ratio()),
+            ops_per_sec: None,
+            bytes_per_entry: None,
+        }));
+    }
+
+    match results::save_to(output.as_deref(), "compare", &records) {
+        Ok(dir) => println!("Results written to {}", dir.display()),
+        Err(err) => {
+            eprintln!("Failed to write results: {}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Print latency percentiles of get and insert of every policy during a long zipfian run.
+//! Throughput of the runs is also written to `latency.csv` and `latency.json` in the results
+//! directory, or under `--output <dir>`.
+//!
+//! Usage: `latency [--seed <n>] [--output <dir>]`
+
+use benchmark::{
+    latency,
+    results::{self, Record},
+    workload::{Distribution, KeyGenerator},
+};
+use common::Cache;
+use fused_lru::FusedLru;
+use std::{env, path::PathBuf, process, time::Instant};
+use sync_naive_lru::{ArenaLru, SyncNaiveLru};
+
+const CAPACITY: usize = 100_000;
+const TRACE_LENGTH: usize = 10_000_000;
+const WORKLOAD: &str = "zipf-0.99";
+
+fn run<C: Cache<u64, u64>>(policy: &str, mut cache: C, trace: &[u64]) -> Record {
+    let start = Instant::now();
+    let latencies = latency::record(&mut cache, trace.iter().copied());
+    let elapsed = start.elapsed();
+    print!("{}", latency::format_rows(policy, &latencies));
+
+    let operations = latencies.get.len() + latencies.insert.len();
+    Record {
+        policy: policy.to_string(),
+        capacity: CAPACITY,
+        workload: WORKLOAD.to_string(),
+        ops_per_sec: Some(operations as f64 / elapsed.as_secs_f64()),
+        ..Default::default()
+    }
+}
+
+fn parse_args() -> Result<(u64, Option<PathBuf>), String> {
+    let mut seed = 0;
+    let mut output = None;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} requires a value", flag));
+        match flag.as_str() {
+            "--seed" => seed = value()?.parse().map_err(|_| "--seed requires an integer")?,
+            "--output" => output = Some(PathBuf::from(value()?)),
+            _ => return Err("Usage: latency [--seed <n>] [--output <dir>]".to_string()),
+        }
+    }
+    Ok((seed, output))
+}
+
+fn main() {
+    let (seed, output) = parse_args().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+
+    let distribution = Distribution::Zipf {
+        keys: CAPACITY as u64 * 10,
+        skew: 0.99,
+    };
+    let trace = KeyGenerator::new(distribution, seed)
+        .take(TRACE_LENGTH)
+        .collect::<Vec<_>>();
+
+    print!("{}", latency::format_header());
+    let mut records = vec![
+        run("sync_naive_lru", SyncNaiveLru::new(CAPACITY), &trace),
+        run("arena_lru", ArenaLru::new(CAPACITY), &trace),
+        run("fused_lru", FusedLru::new(CAPACITY), &trace),
+    ];
+
+    let commit = results::current_commit();
+    records
+        .iter_mut()
+        .for_each(|record| record.commit = commit.clone());
+    match results::save_to(output.as_deref(), "latency", &records) {
+        Ok(dir) => println!("Results written to {}", dir.display()),
+        Err(err) => {
+            eprintln!("Failed to write results: {}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Print memory footprint of every policy filled up to each capacity. Results are also written to
+//! `memory.csv` and `memory.json` in the results directory, or under `--output <dir>`.
+//!
+//! Usage: `memory [--output <dir>]`
+
+use benchmark::{
+    alloc::CountingAllocator,
+    results::{self, Record},
+};
+use common::Cache;
+use fused_lru::FusedLru;
+use small_lru::{lru::SMALL_CAPACITY_THRESHOLD, SmallLru};
+use std::{env, path::PathBuf, process};
+use sync_naive_lru::{ArenaLru, SyncNaiveLru};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+const CAPACITIES: [usize; 4] = [100, 1_000, 10_000, 100_000];
+
+/// Fill a cache built by `make` with `u64` keys and values, and print how much memory it holds.
+fn measure<C: Cache<u64, u64>>(
+    policy: &str,
+    capacity: usize,
+    make: impl FnOnce(usize) -> C,
+) -> Record {
+    ALLOCATOR.reset_peak();
+    let before = ALLOCATOR.snapshot();
+    let mut cache = make(capacity);
+    (0..capacity as u64).for_each(|key| cache.insert(key, key));
+    let after = ALLOCATOR.snapshot();
+    drop(cache);
+
+    let bytes = after.current - before.current;
+    println!(
+        "{:<16} {:>10} {:>14} {:>14.1} {:>14} {:>12}",
+        policy,
+        capacity,
+        bytes,
+        bytes as f64 / capacity as f64,
+        after.peak - before.current,
+        after.allocations - before.allocations,
+    );
+    Record {
+        policy: policy.to_string(),
+        capacity,
+        workload: "fill".to_string(),
+        bytes_per_entry: Some(bytes as f64 / capacity as f64),
+        ..Default::default()
+    }
+}
+
+fn parse_args() -> Result<Option<PathBuf>, String> {
+    let mut output = None;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--output" => {
+                output = Some(PathBuf::from(
+                    args.next().ok_or("--output requires a value")?,
+                ));
+            }
+            _ => return Err("Usage: memory [--output <dir>]".to_string()),
+        }
+    }
+    Ok(output)
+}
+
+fn main() {
+    let output = parse_args().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+    println!(
+        "{:<16} {:>10} {:>14} {:>14} {:>14} {:>12}",
+        "policy", "capacity", "bytes", "bytes/entry", "peak bytes", "allocations"
+    );
+    let mut records = Vec::new();
+    for capacity in CAPACITIES {
+        records.push(measure("sync_naive_lru", capacity, SyncNaiveLru::new));
+        records.push(measure("arena_lru", capacity, ArenaLru::new));
+        records.push(measure("fused_lru", capacity, FusedLru::new));
+        if capacity <= SMALL_CAPACITY_THRESHOLD {
+            records.push(measure("small_lru", capacity, SmallLru::new));
+        }
+    }
+
+    let commit = results::current_commit();
+    records
+        .iter_mut()
+        .for_each(|record| record.commit = commit.clone());
+    match results::save_to(output.as_deref(), "memory", &records) {
+        Ok(dir) => println!("Results written to {}", dir.display()),
+        Err(err) => {
+            eprintln!("Failed to write results: {}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Plot a metric of result files against capacity.
+//!
+//! Usage: `plot <hit_ratio|ops_per_sec|bytes_per_entry> <output.svg> <results.csv>...`
+//!
+//! One line is drawn per policy and workload; when the files come from several commits, the
+//! commit is part of the line label as well.
+
+use benchmark::results::{self, Record};
+use plotters::prelude::*;
+use std::{collections::BTreeMap, env, error::Error, process};
+
+fn metric(record: &Record, name: &str) -> Option<f64> {
+    match name {
+        "hit_ratio" => record.hit_ratio,
+        "ops_per_sec" => record.ops_per_sec,
+        "bytes_per_entry" => record.bytes_per_entry,
+        _ => None,
+    }
+}
+
+fn plot(metric_name: &str, output: &str, records: &[Record]) -> Result<(), Box<dyn Error>> {
+    let multiple_commits = records.iter().any(|r| r.commit != records[0].commit);
+    let mut series = BTreeMap::<String, Vec<(f64, f64)>>::new();
+    for record in records {
+        let value = match metric(record, metric_name) {
+            Some(value) => value,
+            None => continue,
+        };
+        let mut label = format!("{} / {}", record.policy, record.workload);
+        if multiple_commits {
+            label = format!("{} @ {}", label, record.commit);
+        }
+        series
+            .entry(label)
+            .or_default()
+            .push((record.capacity as f64, value));
+    }
+    if series.is_empty() {
+        return Err(format!("No record has {}", metric_name).into());
+    }
+    series
+        .values_mut()
+        .for_each(|points| points.sort_by(|a, b| a.0.total_cmp(&b.0)));
+
+    let points = series.values().flatten();
+    let min_x = points.clone().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let max_x = points.clone().map(|p| p.0).fold(1.0, f64::max);
+    let max_y = points.map(|p| p.1).fold(0.0, f64::max);
+
+    let root = SVGBackend::new(output, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(metric_name, ("sans-serif", 24))
+        .margin(16)
+        .x_label_area_size(40)
+        .y_label_area_size(80)
+        .build_cartesian_2d((min_x.max(1.0)..max_x).log_scale(), 0.0..max_y * 1.05)?;
+    chart
+        .configure_mesh()
+        .x_desc("capacity")
+        .y_desc(metric_name)
+        .draw()?;
+    for (i, (label, points)) in series.into_iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        chart
+            .draw_series(LineSeries::new(points, color.stroke_width(2)))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE)
+        .border_style(BLACK)
+        .draw()?;
+    root.present()?;
+    Ok(())
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.len() < 3 {
+        eprintln!(
+            "Usage: plot <hit_ratio|ops_per_sec|bytes_per_entry> <output.svg> <results.csv>..."
+        );
+        process::exit(1);
+    }
+
+    let mut records = Vec::new();
+    for path in &args[2..] {
+        match results::read_csv(path) {
+            Ok(mut read) => records.append(&mut read),
+            Err(err) => {
+                eprintln!("Failed to read {}: {}", path, err);
+                process::exit(1);
+            }
+        }
+    }
+    if let Err(err) = plot(&args[0], &args[1], &records) {
+        eprintln!("Failed to plot: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,153 @@
+//! Run a trace file or a generated workload through registered policies and print a summary.
+//! Results are written as `simulate.csv` and `simulate.json` under the results directory of the
+//! current commit, or under `--output <dir>`, to be read by `plot`.
+//!
+//! Usage: `simulate [--policy <name>|all] --capacity <n> (--trace <file> | --workload <spec>)
+//! [--length <n>] [--seed <n>] [--output <dir>]`
+//!
+//! See `Distribution::from_str` for workload specs, e.g. `zipf:100000:0.99`.
+
+use benchmark::{
+    results::{self, Record},
+    simulator::{self, read_trace, Policy},
+    workload::{Distribution, KeyGenerator},
+};
+use std::{env, path::PathBuf, process};
+
+const DEFAULT_LENGTH: usize = 1_000_000;
+
+struct Args {
+    policies: Vec<Policy>,
+    capacity: usize,
+    trace: Vec<u64>,
+    source: String,
+    seed: u64,
+    output: Option<PathBuf>,
+}
+
+fn usage() -> String {
+    let names = simulator::policies()
+        .iter()
+        .map(|policy| policy.name)
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "Usage: simulate [--policy <name>|all] --capacity <n> (--trace <file> | --workload <spec>) \
+         [--length <n>] [--seed <n>] [--output <dir>]\nPolicies: {}",
+        names
+    )
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut policy = "all".to_string();
+    let mut capacity = None;
+    let mut trace_path = None;
+    let mut workload = None;
+    let mut length = DEFAULT_LENGTH;
+    let mut seed = 0;
+    let mut output = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} requires a value", flag));
+        match flag.as_str() {
+            "--policy" => policy = value()?,
+            "--capacity" => {
+                capacity = Some(value()?.parse().map_err(|_| "Invalid capacity")?);
+            }
+            "--trace" => trace_path = Some(value()?),
+            "--workload" => workload = Some(value()?.parse::<Distribution>()?),
+            "--length" => length = value()?.parse().map_err(|_| "Invalid length")?,
+            "--seed" => seed = value()?.parse().map_err(|_| "Invalid seed")?,
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "--help" | "-h" => return Err(usage()),
+            _ => return Err(format!("Unknown flag: {}", flag)),
+        }
+    }
+
+    let policies = if policy == "all" {
+        simulator::policies()
+    } else {
+        vec![simulator::policy(&policy).ok_or(format!("Unknown policy: {}", policy))?]
+    };
+    let capacity = capacity.ok_or("--capacity is required")?;
+    let (trace, source) = match (trace_path, workload) {
+        (Some(path), None) => {
+            let trace =
+                read_trace(&path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+            (trace, path)
+        }
+        (None, Some(distribution)) => {
+            let source = format!("{:?}", distribution);
+            let trace = KeyGenerator::new(distribution, seed).take(length).collect();
+            (trace, source)
+        }
+        _ => return Err("Exactly one of --trace or --workload is required".to_string()),
+    };
+
+    Ok(Args {
+        policies,
+        capacity,
+        trace,
+        source,
+        seed,
+        output,
+    })
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+    };
+
+    println!("trace:    {} ({} accesses)", args.source, args.trace.len());
+    println!("capacity: {}", args.capacity);
+    println!("seed:     {}", args.seed);
+    println!();
+    println!(
+        "{:<16} {:>12} {:>12} {:>10} {:>12} {:>14}",
+        "policy", "hits", "misses", "hit ratio", "evictions", "ops/sec"
+    );
+    let commit = results::current_commit();
+    let mut records = Vec::new();
+    for policy in &args.policies {
+        if args.capacity > policy.max_capacity {
+            eprintln!(
+                "Skipping {}: capacity is above {}",
+                policy.name, policy.max_capacity
+            );
+            continue;
+        }
+        let row = simulator::run_policy(policy, args.capacity, args.seed, &args.trace);
+        println!(
+            "{:<16} {:>12} {:>12} {:>9.2}% {:>12} {:>14.0}",
+            row.policy,
+            row.stats.hits,
+            row.stats.misses,
+            row.stats.hit_ratio() * 100.0,
+            row.stats.evictions,
+            row.ops_per_sec()
+        );
+        records.push(Record {
+            commit: commit.clone(),
+            policy: row.policy.to_string(),
+            capacity: row.capacity,
+            workload: args.source.clone(),
+            hit_ratio: Some(row.stats.hit_ratio()),
+            ops_per_sec: Some(row.ops_per_sec()),
+            bytes_per_entry: None,
+        });
+    }
+
+    match results::save_to(args.output.as_deref(), "simulate", &records) {
+        Ok(dir) => println!("Results written to {}", dir.display()),
+        Err(err) => {
+            eprintln!("Failed to write results: {}", err);
+            process::exit(1);
+        }
+    }
+}
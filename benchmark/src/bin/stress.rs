@@ -0,0 +1,136 @@
+//! Run millions of random operations through every policy at several capacities, checking
+//! invariants and live value counts as it goes. Each configuration runs on its own thread.
+//! Throughput of the configurations which passed is written as `stress.csv` and `stress.json`
+//! under the results directory of the current commit, or under `--output <dir>`.
+//!
+//! Usage: `stress [--operations <n>] [--seed <n>] [--output <dir>]`
+
+use benchmark::{
+    results::{self, Record},
+    stress::{self, Config, Report},
+};
+use fused_lru::FusedLru;
+use small_lru::{lru::SMALL_CAPACITY_THRESHOLD, SmallLru};
+use std::{env, path::PathBuf, process, thread, time::Instant};
+use sync_naive_lru::{ArenaLru, SyncNaiveLru};
+
+const DEFAULT_OPERATIONS: u64 = 5_000_000;
+const CAPACITIES: [usize; 4] = [0, 1, 64, 10000];
+
+type Target = fn(&Config) -> Report;
+
+fn targets() -> Vec<(&'static str, usize, Target)> {
+    vec![
+        ("sync_naive_lru", usize::MAX, |config| {
+            stress::run(SyncNaiveLru::new, SyncNaiveLru::validate, config)
+        }),
+        ("arena_lru", usize::MAX, |config| {
+            stress::run(ArenaLru::new, |_| {}, config)
+        }),
+        ("fused_lru", usize::MAX, |config| {
+            stress::run(FusedLru::new, |_| {}, config)
+        }),
+        ("small_lru", SMALL_CAPACITY_THRESHOLD, |config| {
+            stress::run(SmallLru::new, |_| {}, config)
+        }),
+    ]
+}
+
+fn parse_args() -> Result<(u64, u64, Option<PathBuf>), String> {
+    let mut operations = DEFAULT_OPERATIONS;
+    let mut seed = 0;
+    let mut output = None;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} requires a value", flag));
+        match flag.as_str() {
+            "--operations" => {
+                operations = value()?.parse().map_err(|_| "Invalid operations")?;
+            }
+            "--seed" => seed = value()?.parse().map_err(|_| "Invalid seed")?,
+            "--output" => output = Some(PathBuf::from(value()?)),
+            _ => {
+                return Err(
+                    "Usage: stress [--operations <n>] [--seed <n>] [--output <dir>]".to_string(),
+                )
+            }
+        }
+    }
+    Ok((operations, seed, output))
+}
+
+fn main() {
+    let (operations, seed, output) = parse_args().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+
+    let mut handles = Vec::new();
+    for (name, max_capacity, target) in targets() {
+        for capacity in CAPACITIES {
+            if capacity > max_capacity {
+                continue;
+            }
+            let config = Config {
+                capacity,
+                // Enough keys for evictions to happen on most operations of small caches.
+                keys: (capacity as u64 * 4).max(16),
+                operations,
+                check_interval: 10000,
+                seed,
+            };
+            let handle = thread::spawn(move || {
+                let start = Instant::now();
+                let report = target(&config);
+                (report, start.elapsed())
+            });
+            handles.push((name, capacity, handle));
+        }
+    }
+
+    println!(
+        "{:<16} {:>10} {:>12} {:>8} {:>10} {:>10}",
+        "policy", "capacity", "operations", "checks", "max len", "seconds"
+    );
+    let commit = results::current_commit();
+    let mut records = Vec::new();
+    let mut failed = false;
+    for (name, capacity, handle) in handles {
+        match handle.join() {
+            Ok((report, elapsed)) => {
+                println!(
+                    "{:<16} {:>10} {:>12} {:>8} {:>10} {:>10.2}",
+                    name,
+                    capacity,
+                    report.operations,
+                    report.checks,
+                    report.max_len,
+                    elapsed.as_secs_f64()
+                );
+                records.push(Record {
+                    commit: commit.clone(),
+                    policy: name.to_string(),
+                    capacity,
+                    workload: "stress".to_string(),
+                    hit_ratio: None,
+                    ops_per_sec: Some(report.operations as f64 / elapsed.as_secs_f64()),
+                    bytes_per_entry: None,
+                });
+            }
+            Err(_) => {
+                println!("{:<16} {:>10} FAILED", name, capacity);
+                failed = true;
+            }
+        }
+    }
+    match results::save_to(output.as_deref(), "stress", &records) {
+        Ok(dir) => println!("Results written to {}", dir.display()),
+        Err(err) => {
+            eprintln!("Failed to write results: {}", err);
+            process::exit(1);
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
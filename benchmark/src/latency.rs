@@ -0,0 +1,95 @@
+use common::Cache;
+use hdrhistogram::Histogram;
+use std::{fmt::Write as _, time::Instant};
+
+/// Highest latency recorded, in nanoseconds; slower operations are clamped to it.
+const MAX_LATENCY_NS: u64 = 1_000_000_000;
+
+/// Per-operation latencies in nanoseconds.
+pub struct Latencies {
+    pub get: Histogram<u64>,
+    pub insert: Histogram<u64>,
+}
+
+impl Latencies {
+    pub fn new() -> Self {
+        let histogram =
+            || Histogram::new_with_bounds(1, MAX_LATENCY_NS, 3).expect("Bounds must be valid");
+        Self {
+            get: histogram(),
+            insert: histogram(),
+        }
+    }
+}
+
+impl Default for Latencies {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn elapsed_ns(start: Instant) -> u64 {
+    (start.elapsed().as_nanos() as u64).clamp(1, MAX_LATENCY_NS)
+}
+
+/// Run `trace` through `cache` as a look-aside cache and record latency of every operation.
+/// Each latency includes the overhead of reading the clock, which is a few tens of nanoseconds.
+pub fn record<C, I>(cache: &mut C, trace: I) -> Latencies
+where
+    C: Cache<u64, u64>,
+    I: IntoIterator<Item = u64>,
+{
+    let mut latencies = Latencies::new();
+    for key in trace {
+        let start = Instant::now();
+        let hit = cache.get(&key).is_some();
+        latencies.get.saturating_record(elapsed_ns(start));
+        if !hit {
+            let start = Instant::now();
+            cache.insert(key, key);
+            latencies.insert.saturating_record(elapsed_ns(start));
+        }
+    }
+    latencies
+}
+
+pub fn format_header() -> String {
+    format!(
+        "{:<16} {:<8} {:>10} {:>10} {:>10} {:>10} {:>10}\n",
+        "policy", "op", "count", "p50 ns", "p99 ns", "p999 ns", "max ns"
+    )
+}
+
+pub fn format_rows(policy: &str, latencies: &Latencies) -> String {
+    let mut rows = String::new();
+    for (op, histogram) in [("get", &latencies.get), ("insert", &latencies.insert)] {
+        writeln!(
+            rows,
+            "{:<16} {:<8} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            policy,
+            op,
+            histogram.len(),
+            histogram.value_at_quantile(0.5),
+            histogram.value_at_quantile(0.99),
+            histogram.value_at_quantile(0.999),
+            histogram.max(),
+        )
+        .unwrap();
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sync_naive_lru::SyncNaiveLru;
+
+    #[test]
+    fn record_every_operation() {
+        let mut lru = SyncNaiveLru::new(2);
+        let latencies = record(&mut lru, [1, 2, 1, 3, 2]);
+        assert_eq!(latencies.get.len(), 5);
+        assert_eq!(latencies.insert.len(), 4);
+        assert!(latencies.get.min() >= 1);
+    }
+}
@@ -0,0 +1,8 @@
+pub mod alloc;
+pub mod latency;
+pub mod results;
+pub mod scan;
+pub mod simulator;
+pub mod stress;
+pub mod workload;
+pub mod ycsb;
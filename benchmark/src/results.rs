@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Environment variable overriding the directory results are written to.
+pub const RESULTS_DIR_ENV: &str = "RESULTS_DIR";
+const DEFAULT_RESULTS_DIR: &str = "results";
+
+/// One measurement of a policy, as written to result files.
+/// Metrics not measured by the producing binary are left empty.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// Commit of the workspace the measurement was taken at.
+    pub commit: String,
+    pub policy: String,
+    pub capacity: usize,
+    pub workload: String,
+    pub hit_ratio: Option<f64>,
+    pub ops_per_sec: Option<f64>,
+    pub bytes_per_entry: Option<f64>,
+}
+
+/// Abbreviated hash of `HEAD`, or `unknown` outside of a git checkout.
+pub fn current_commit() -> String {
+    Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Directory for results of `commit`: `$RESULTS_DIR/<commit>`, where `RESULTS_DIR` defaults to
+/// `results`.
+pub fn results_dir(commit: &str) -> PathBuf {
+    let base = env::var_os(RESULTS_DIR_ENV).unwrap_or_else(|| DEFAULT_RESULTS_DIR.into());
+    PathBuf::from(base).join(commit)
+}
+
+/// Write `records` to `<dir>/<name>.csv` and `<dir>/<name>.json`.
+pub fn write_records(dir: &Path, name: &str, records: &[Record]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let mut writer = csv::Writer::from_path(dir.join(format!("{}.csv", name)))?;
+    for record in records {
+        writer.serialize(record).map_err(io::Error::other)?;
+    }
+    writer.flush()?;
+
+    let file = File::create(dir.join(format!("{}.json", name)))?;
+    serde_json::to_writer_pretty(file, records).map_err(io::Error::other)
+}
+
+/// Write `records` of this run under the results directory of the current commit and return
+/// the directory.
+pub fn save(name: &str, records: &[Record]) -> io::Result<PathBuf> {
+    let dir = results_dir(&current_commit());
+    write_records(&dir, name, records)?;
+    Ok(dir)
+}
+
+/// Same as `save`, but writing into `dir` when given, e.g. by an `--output` flag.
+pub fn save_to(dir: Option<&Path>, name: &str, records: &[Record]) -> io::Result<PathBuf> {
+    match dir {
+        Some(dir) => {
+            write_records(dir, name, records)?;
+            Ok(dir.to_path_buf())
+        }
+        None => save(name, records),
+    }
+}
+
+pub fn read_csv<P: AsRef<Path>>(path: P) -> io::Result<Vec<Record>> {
+    csv::Reader::from_path(path)?
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_csv_and_json() {
+        let dir = env::temp_dir().join("cache_experiment_results");
+        let records = vec![
+            Record {
+                commit: "abc1234".to_string(),
+                policy: "sync_naive_lru".to_string(),
+                capacity: 100,
+                workload: "zipf".to_string(),
+                hit_ratio: Some(0.5),
+                ops_per_sec: Some(1e6),
+                bytes_per_entry: None,
+            },
+            Record {
+                policy: "fused_lru".to_string(),
+                bytes_per_entry: Some(128.0),
+                ..Default::default()
+            },
+        ];
+        write_records(&dir, "test", &records).unwrap();
+
+        assert_eq!(read_csv(dir.join("test.csv")).unwrap(), records);
+        let json = fs::read_to_string(dir.join("test.json")).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Record>>(&json).unwrap(), records);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn save_to_given_dir() {
+        let dir = env::temp_dir().join("cache_experiment_output");
+        let records = vec![Record::default()];
+        assert_eq!(save_to(Some(&dir), "test", &records).unwrap(), dir);
+        assert_eq!(read_csv(dir.join("test.csv")).unwrap(), records);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+use common::rng::Rng;
+use std::fmt::Write as _;
+
+use crate::simulator::{self, simulate_with, Stats};
+
+/// Scenario interleaving accesses to a hot working set with periodic full sequential scans.
+/// Hot keys are `0..hot_keys` and scanned keys follow them, so a scan never touches the hot set.
+///
+/// LRU lets every scan flush the hot set once the scan is longer than the spare capacity; a
+/// scan-resistant policy keeps serving the hot set across scans.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScanScenario {
+    pub hot_keys: u64,
+    pub scan_keys: u64,
+    /// Uniformly random accesses to the hot set between two scans.
+    pub hot_accesses_per_round: usize,
+    pub rounds: usize,
+    pub seed: u64,
+}
+
+impl Default for ScanScenario {
+    fn default() -> Self {
+        Self {
+            hot_keys: 1_000,
+            scan_keys: 10_000,
+            hot_accesses_per_round: 2_000,
+            rounds: 20,
+            seed: 0,
+        }
+    }
+}
+
+impl ScanScenario {
+    pub fn trace(&self) -> Vec<u64> {
+        let mut rng = Rng::new(self.seed);
+        let mut trace = Vec::new();
+        for _ in 0..self.rounds {
+            trace.extend((0..self.hot_accesses_per_round).map(|_| rng.below(self.hot_keys)));
+            trace.extend(self.hot_keys..self.hot_keys + self.scan_keys);
+        }
+        trace
+    }
+
+    pub fn is_hot(&self, key: u64) -> bool {
+        key < self.hot_keys
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Row {
+    pub policy: &'static str,
+    pub capacity: usize,
+    /// Hits and misses of accesses to the hot set only.
+    pub hot: Stats,
+    pub overall: Stats,
+}
+
+/// Run `scenario` through every registered policy at each of `capacities`.
+pub fn compare(scenario: &ScanScenario, capacities: &[usize]) -> Vec<Row> {
+    let trace = scenario.trace();
+    let mut rows = Vec::new();
+    for &capacity in capacities {
+        for policy in simulator::policies() {
+            if capacity > policy.max_capacity {
+                continue;
+            }
+            let mut hot = Stats::default();
+            let mut cache = (policy.build)(capacity, scenario.seed);
+            let overall = simulate_with(&mut cache, trace.iter().copied(), |key, hit| {
+                if !scenario.is_hot(key) {
+                    return;
+                }
+                if hit {
+                    hot.hits += 1;
+                } else {
+                    hot.misses += 1;
+                }
+            });
+            rows.push(Row {
+                policy: policy.name,
+                capacity,
+                hot,
+                overall,
+            });
+        }
+    }
+    rows
+}
+
+pub fn format_table(rows: &[Row]) -> String {
+    let mut table = format!(
+        "{:<16} {:>10} {:>14} {:>14}\n",
+        "policy", "capacity", "hot hit ratio", "hit ratio"
+    );
+    for row in rows {
+        writeln!(
+            table,
+            "{:<16} {:>10} {:>13.2}% {:>13.2}%",
+            row.policy,
+            row.capacity,
+            row.hot.hit_ratio() * 100.0,
+            row.overall.hit_ratio() * 100.0
+        )
+        .unwrap();
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario() -> ScanScenario {
+        ScanScenario {
+            hot_keys: 10,
+            scan_keys: 100,
+            hot_accesses_per_round: 100,
+            rounds: 5,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn trace_interleaves_hot_accesses_and_scans() {
+        let scenario = scenario();
+        let trace = scenario.trace();
+        assert_eq!(trace.len(), 5 * (100 + 100));
+        assert!(trace[..100].iter().all(|&key| scenario.is_hot(key)));
+        assert_eq!(trace[100..200], (10..110).collect::<Vec<_>>()[..]);
+    }
+
+    #[test]
+    fn scans_flush_hot_set_from_lru() {
+        let scenario = scenario();
+        let rows = compare(&scenario, &[50]);
+        let lru = rows
+            .iter()
+            .find(|row| row.policy == "sync_naive_lru")
+            .unwrap();
+        // Every round starts by missing the whole hot set again.
+        assert_eq!(lru.hot.hits + lru.hot.misses, 500);
+        assert_eq!(lru.hot.misses, 5 * 10);
+        assert_eq!(lru.overall.hits, lru.hot.hits);
+    }
+
+    #[test]
+    fn lfu_keeps_hot_set_through_scans() {
+        let rows = compare(&scenario(), &[50]);
+        for row in rows.iter().filter(|row| row.policy.starts_with("lfu")) {
+            // Only the first round misses the hot set.
+            assert_eq!(row.hot.misses, 10, "{}", row.policy);
+        }
+    }
+}
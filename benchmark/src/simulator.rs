@@ -0,0 +1,271 @@
+use common::{builder::TieBreak, Cache};
+use fused_lru::FusedLru;
+use lfu::Lfu;
+use small_lru::{lru::SMALL_CAPACITY_THRESHOLD, SmallLru};
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    time::{Duration, Instant},
+};
+use sync_naive_lru::{ArenaLru, SyncNaiveLru};
+
+/// Outcome of running a trace through a cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl Stats {
+    pub fn hit_ratio(&self) -> f64 {
+        let accesses = self.hits + self.misses;
+        if accesses == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / accesses as f64
+    }
+}
+
+/// Run `trace` through `cache` used as a look-aside cache: every key is looked up and inserted
+/// when it misses.
+/// A miss is counted as an eviction when inserting the key does not grow the cache.
+pub fn simulate<C, I>(cache: &mut C, trace: I) -> Stats
+where
+    C: Cache<u64, u64>,
+    I: IntoIterator<Item = u64>,
+{
+    simulate_with(cache, trace, |_, _| {})
+}
+
+/// Same as `simulate`, calling `observe` with every key and whether it hit.
+pub fn simulate_with<C, I, F>(cache: &mut C, trace: I, mut observe: F) -> Stats
+where
+    C: Cache<u64, u64>,
+    I: IntoIterator<Item = u64>,
+    F: FnMut(u64, bool),
+{
+    let mut stats = Stats::default();
+    for key in trace {
+        let hit = cache.get(&key).is_some();
+        observe(key, hit);
+        if hit {
+            stats.hits += 1;
+            continue;
+        }
+        stats.misses += 1;
+        let len = cache.len();
+        cache.insert(key, key);
+        if cache.len() <= len {
+            stats.evictions += 1;
+        }
+    }
+    stats
+}
+
+/// Cache policy registered to the simulator.
+pub struct Policy {
+    pub name: &'static str,
+    /// Largest capacity the policy is meant for; the simulator skips larger ones.
+    pub max_capacity: usize,
+    /// Build a cache of the given capacity. Randomized policies draw their random numbers from
+    /// the given seed, so that runs are reproducible.
+    pub build: fn(usize, u64) -> Box<dyn Cache<u64, u64>>,
+}
+
+/// Every policy of this workspace.
+pub fn policies() -> Vec<Policy> {
+    vec![
+        Policy {
+            name: "sync_naive_lru",
+            max_capacity: usize::MAX,
+            build: |capacity, _seed| Box::new(SyncNaiveLru::new(capacity)),
+        },
+        Policy {
+            name: "arena_lru",
+            max_capacity: usize::MAX,
+            build: |capacity, _seed| Box::new(ArenaLru::new(capacity)),
+        },
+        Policy {
+            name: "fused_lru",
+            max_capacity: usize::MAX,
+            build: |capacity, _seed| Box::new(FusedLru::new(capacity)),
+        },
+        Policy {
+            name: "small_lru",
+            max_capacity: SMALL_CAPACITY_THRESHOLD,
+            build: |capacity, _seed| Box::new(SmallLru::new(capacity)),
+        },
+        Policy {
+            name: "lfu_lru",
+            max_capacity: usize::MAX,
+            build: |capacity, _seed| Box::new(Lfu::with_tie_break(capacity, TieBreak::Lru)),
+        },
+        Policy {
+            name: "lfu_fifo",
+            max_capacity: usize::MAX,
+            build: |capacity, _seed| Box::new(Lfu::with_tie_break(capacity, TieBreak::Fifo)),
+        },
+        Policy {
+            name: "lfu_random",
+            max_capacity: usize::MAX,
+            build: |capacity, seed| {
+                Box::new(Lfu::with_tie_break(capacity, TieBreak::Random { seed }))
+            },
+        },
+    ]
+}
+
+/// Look up a registered policy by name.
+pub fn policy(name: &str) -> Option<Policy> {
+    policies().into_iter().find(|policy| policy.name == name)
+}
+
+/// One row of the comparison table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Row {
+    pub policy: &'static str,
+    pub capacity: usize,
+    pub stats: Stats,
+    /// Wall-clock time the run took.
+    pub elapsed: Duration,
+}
+
+impl Row {
+    /// Accesses per second during the run, including inserts on misses.
+    pub fn ops_per_sec(&self) -> f64 {
+        (self.stats.hits + self.stats.misses) as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Run `trace` through `policy` with `capacity`, timing the run.
+pub fn run_policy(policy: &Policy, capacity: usize, seed: u64, trace: &[u64]) -> Row {
+    let start = Instant::now();
+    let mut cache = (policy.build)(capacity, seed);
+    let stats = simulate(&mut cache, trace.iter().copied());
+    Row {
+        policy: policy.name,
+        capacity,
+        stats,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Run `trace` through every registered policy at each of `capacities`.
+pub fn compare(trace: &[u64], capacities: &[usize], seed: u64) -> Vec<Row> {
+    let mut rows = Vec::new();
+    for &capacity in capacities {
+        for policy in policies() {
+            if capacity <= policy.max_capacity {
+                rows.push(run_policy(&policy, capacity, seed, trace));
+            }
+        }
+    }
+    rows
+}
+
+pub fn format_table(rows: &[Row]) -> String {
+    let mut table = format!(
+        "{:<16} {:>10} {:>10} {:>12}\n",
+        "policy", "capacity", "hit ratio", "evictions"
+    );
+    for row in rows {
+        writeln!(
+            table,
+            "{:<16} {:>10} {:>9.2}% {:>12}",
+            row.policy,
+            row.capacity,
+            row.stats.hit_ratio() * 100.0,
+            row.stats.evictions
+        )
+        .unwrap();
+    }
+    table
+}
+
+/// Read a trace file holding one key per line.
+/// The first whitespace-separated token of each line is the key; distinct tokens are numbered in
+/// order of appearance, so keys need not be integers. Empty lines and lines starting with `#` are
+/// skipped.
+pub fn read_trace<P: AsRef<Path>>(path: P) -> io::Result<Vec<u64>> {
+    let mut ids = HashMap::new();
+    let mut trace = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        let token = match line.split_whitespace().next() {
+            Some(token) if !token.starts_with('#') => token,
+            _ => continue,
+        };
+        let next_id = ids.len() as u64;
+        trace.push(*ids.entry(token.to_string()).or_insert(next_id));
+    }
+    Ok(trace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workload::{Distribution, KeyGenerator};
+
+    #[test]
+    fn simulate_counts_hits_and_evictions() {
+        let mut lru = SyncNaiveLru::new(2);
+        let stats = simulate(&mut lru, [1, 2, 1, 3, 2]);
+        assert_eq!(
+            stats,
+            Stats {
+                hits: 1,
+                misses: 4,
+                evictions: 2,
+            }
+        );
+        assert_eq!(stats.hit_ratio(), 0.2);
+    }
+
+    #[test]
+    fn exact_lru_policies_agree() {
+        let trace = (0..1000).map(|i| i * i % 37).collect::<Vec<_>>();
+        let rows = compare(&trace, &[4, 16], 0);
+        assert_eq!(rows.len(), 2 * policies().len());
+        for row in rows.iter().filter(|row| !row.policy.starts_with("lfu")) {
+            let expected = simulate(&mut SyncNaiveLru::new(row.capacity), trace.iter().copied());
+            assert_eq!(row.stats, expected, "{}", row.policy);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_stats() {
+        let run = |seed| {
+            let distribution = Distribution::Zipf {
+                keys: 1000,
+                skew: 0.99,
+            };
+            let trace = KeyGenerator::new(distribution, seed)
+                .take(10000)
+                .collect::<Vec<_>>();
+            compare(&trace, &[16, 100], seed)
+                .into_iter()
+                .map(|row| (row.policy, row.capacity, row.stats))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1), run(2));
+    }
+
+    #[test]
+    fn skip_policies_beyond_max_capacity() {
+        let rows = compare(&[1, 2, 3], &[SMALL_CAPACITY_THRESHOLD + 1], 0);
+        assert!(rows.iter().all(|row| row.policy != "small_lru"));
+    }
+
+    #[test]
+    fn read_trace_numbers_tokens() {
+        let path = std::env::temp_dir().join("cache_experiment_read_trace.txt");
+        std::fs::write(&path, "# comment\na 1\nb\n\na\nc 2\n").unwrap();
+        assert_eq!(read_trace(&path).unwrap(), vec![0, 1, 0, 2]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+//! Long-running randomized operations checking that caches neither break nor leak.
+//!
+//! Values stored in caches are `Tracked`, which count how many of them are alive. Since a cache
+//! is the only owner of its values, the count must equal the cache's `len` at any time and drop to
+//! zero once the cache is dropped; a drift means an entry is leaked (e.g. by an `Rc` cycle in
+//! the list) or dropped twice.
+
+use common::{rng::Rng, Cache};
+use std::{cell::Cell, rc::Rc};
+
+/// Value counting live instances of itself in a shared counter.
+#[derive(Debug)]
+pub struct Tracked {
+    live: Rc<Cell<usize>>,
+}
+
+impl Tracked {
+    pub fn new(live: &Rc<Cell<usize>>) -> Self {
+        live.set(live.get() + 1);
+        Self {
+            live: Rc::clone(live),
+        }
+    }
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        Self::new(&self.live)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.live.set(self.live.get() - 1);
+    }
+}
+
+/// Parameters of one stress run.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub capacity: usize,
+    /// Keys are drawn uniformly from `0..keys`.
+    pub keys: u64,
+    pub operations: u64,
+    /// Counts and invariants are checked every this many operations.
+    pub check_interval: u64,
+    pub seed: u64,
+}
+
+/// What a stress run went through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub operations: u64,
+    pub checks: u64,
+    pub max_len: usize,
+}
+
+/// Run `config.operations` random inserts, gets, removes and clears through a cache built by
+/// `make`, calling `validate` on it periodically.
+/// Panics when the number of live values drifts from the cache's `len`, exceeds the capacity or
+/// is not zero after the cache is dropped.
+pub fn run<C, F, G>(make: F, validate: G, config: &Config) -> Report
+where
+    C: Cache<u64, Tracked>,
+    F: FnOnce(usize) -> C,
+    G: Fn(&C),
+{
+    let live = Rc::new(Cell::new(0));
+    let mut rng = Rng::new(config.seed);
+    let mut cache = make(config.capacity);
+    let mut report = Report::default();
+
+    let check = |cache: &C, report: &mut Report| {
+        validate(cache);
+        assert_eq!(
+            live.get(),
+            cache.len(),
+            "Live values drifted from len after {} operations",
+            report.operations
+        );
+        assert!(
+            cache.len() <= config.capacity,
+            "More elements than capacity"
+        );
+        report.max_len = report.max_len.max(cache.len());
+        report.checks += 1;
+    };
+
+    for _ in 0..config.operations {
+        let key = rng.below(config.keys);
+        match rng.below(1000) {
+            0 => cache.clear(),
+            1..=100 => {
+                cache.remove(&key);
+            }
+            101..=500 => cache.insert(key, Tracked::new(&live)),
+            _ => {
+                cache.get(&key);
+            }
+        }
+        report.operations += 1;
+        if report.operations % config.check_interval == 0 {
+            check(&cache, &mut report);
+        }
+    }
+    check(&cache, &mut report);
+
+    drop(cache);
+    assert_eq!(live.get(), 0, "Values leaked after the cache is dropped");
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sync_naive_lru::SyncNaiveLru;
+
+    #[test]
+    fn tracked_counts_live_values() {
+        let live = Rc::new(Cell::new(0));
+        let value = Tracked::new(&live);
+        let copy = value.clone();
+        assert_eq!(live.get(), 2);
+        drop(value);
+        drop(copy);
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn run_sync_naive_lru() {
+        let config = Config {
+            capacity: 16,
+            keys: 64,
+            operations: 10000,
+            check_interval: 100,
+            seed: 0,
+        };
+        let report = run(SyncNaiveLru::new, SyncNaiveLru::validate, &config);
+        assert_eq!(report.operations, 10000);
+        assert_eq!(report.checks, 101);
+        assert_eq!(report.max_len, 16);
+    }
+
+    /// Cache keeping every value it removes alive, as a leaking list would.
+    struct Leaky {
+        inner: SyncNaiveLru<u64, Tracked>,
+        leaked: Vec<Tracked>,
+    }
+
+    impl Cache<u64, Tracked> for Leaky {
+        fn insert(&mut self, key: u64, value: Tracked) {
+            self.inner.insert(key, value);
+        }
+
+        fn get(&mut self, key: &u64) -> Option<Tracked> {
+            self.inner.get(key)
+        }
+
+        fn get_quiet(&self, key: &u64) -> Option<Tracked> {
+            self.inner.get_quiet(key)
+        }
+
+        fn remove(&mut self, key: &u64) -> Option<Tracked> {
+            let value = self.inner.remove(key)?;
+            self.leaked.push(value.clone());
+            Some(value)
+        }
+
+        fn clear(&mut self) {
+            self.inner.clear();
+        }
+
+        fn len(&self) -> usize {
+            self.inner.len()
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Live values drifted")]
+    fn detect_leak() {
+        let config = Config {
+            capacity: 16,
+            keys: 64,
+            operations: 10000,
+            check_interval: 100,
+            seed: 0,
+        };
+        let make = |capacity| Leaky {
+            inner: SyncNaiveLru::new(capacity),
+            leaked: Vec::new(),
+        };
+        run(make, |_| {}, &config);
+    }
+}
@@ -0,0 +1,256 @@
+use common::rng::Rng;
+use std::str::FromStr;
+
+/// Distribution of keys drawn by `KeyGenerator`.
+/// Keys are `u64` in `0..keys`; for skewed distributions smaller keys are the hotter ones.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Distribution {
+    /// Every key is equally likely.
+    Uniform { keys: u64 },
+    /// Key of rank `i` (starting from 1) is drawn with probability proportional to `1 / i^skew`.
+    Zipf { keys: u64, skew: f64 },
+    /// `hot_fraction` of the keys receive `hot_probability` of the accesses.
+    Hotspot {
+        keys: u64,
+        hot_fraction: f64,
+        hot_probability: f64,
+    },
+    /// Keys `0, 1, ..., keys - 1` in order, then wrap around.
+    Sequential { keys: u64 },
+}
+
+impl Distribution {
+    pub fn keys(&self) -> u64 {
+        match *self {
+            Distribution::Uniform { keys }
+            | Distribution::Zipf { keys, .. }
+            | Distribution::Hotspot { keys, .. }
+            | Distribution::Sequential { keys } => keys,
+        }
+    }
+}
+
+/// Parse a distribution from a spec of the form `<name>:<parameters>`:
+/// `uniform:KEYS`, `zipf:KEYS:SKEW`, `hotspot:KEYS:HOT_FRACTION:HOT_PROBABILITY` or
+/// `sequential:KEYS`.
+impl FromStr for Distribution {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut parts = spec.split(':');
+        let name = parts.next().unwrap_or_default();
+        let parameters = parts.collect::<Vec<_>>();
+        let invalid = || format!("Invalid workload spec: {}", spec);
+        let keys = || -> Result<u64, String> {
+            match parameters.first().map(|keys| keys.parse()) {
+                Some(Ok(keys)) if keys > 0 => Ok(keys),
+                _ => Err(invalid()),
+            }
+        };
+        let float = |i: usize| -> Result<f64, String> {
+            parameters
+                .get(i)
+                .and_then(|p| p.parse().ok())
+                .ok_or_else(invalid)
+        };
+
+        let distribution = match (name, parameters.len()) {
+            ("uniform", 1) => Distribution::Uniform { keys: keys()? },
+            ("zipf", 2) => Distribution::Zipf {
+                keys: keys()?,
+                skew: float(1)?,
+            },
+            ("hotspot", 3) => Distribution::Hotspot {
+                keys: keys()?,
+                hot_fraction: float(1)?,
+                hot_probability: float(2)?,
+            },
+            ("sequential", 1) => Distribution::Sequential { keys: keys()? },
+            _ => return Err(invalid()),
+        };
+        Ok(distribution)
+    }
+}
+
+/// Infinite stream of keys following a `Distribution`.
+/// Generators built with the same distribution and seed yield the same keys.
+pub struct KeyGenerator {
+    distribution: Distribution,
+    rng: Rng,
+    /// Cumulative probabilities of each rank for `Distribution::Zipf`.
+    zipf_cdf: Vec<f64>,
+    cursor: u64,
+}
+
+impl KeyGenerator {
+    pub fn new(distribution: Distribution, seed: u64) -> Self {
+        assert!(distribution.keys() > 0, "Key space must not be empty");
+        let zipf_cdf = match distribution {
+            Distribution::Zipf { keys, skew } => {
+                let mut sum = 0.0;
+                let mut cdf = (1..=keys)
+                    .map(|rank| {
+                        sum += 1.0 / (rank as f64).powf(skew);
+                        sum
+                    })
+                    .collect::<Vec<_>>();
+                cdf.iter_mut().for_each(|p| *p /= sum);
+                cdf
+            }
+            _ => Vec::new(),
+        };
+        Self {
+            distribution,
+            rng: Rng::new(seed),
+            zipf_cdf,
+            cursor: 0,
+        }
+    }
+
+    pub fn next_key(&mut self) -> u64 {
+        match self.distribution {
+            Distribution::Uniform { keys } => self.rng.below(keys),
+            Distribution::Zipf { keys, .. } => {
+                let p = self.rng.next_f64();
+                (self.zipf_cdf.partition_point(|&c| c < p) as u64).min(keys - 1)
+            }
+            Distribution::Hotspot {
+                keys,
+                hot_fraction,
+                hot_probability,
+            } => {
+                let hot_keys = ((keys as f64 * hot_fraction) as u64).clamp(1, keys);
+                if self.rng.next_f64() < hot_probability || hot_keys == keys {
+                    self.rng.below(hot_keys)
+                } else {
+                    hot_keys + self.rng.below(keys - hot_keys)
+                }
+            }
+            Distribution::Sequential { keys } => {
+                let key = self.cursor;
+                self.cursor = (self.cursor + 1) % keys;
+                key
+            }
+        }
+    }
+}
+
+impl Iterator for KeyGenerator {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        Some(self.next_key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distributions() -> Vec<Distribution> {
+        vec![
+            Distribution::Uniform { keys: 100 },
+            Distribution::Zipf {
+                keys: 100,
+                skew: 0.99,
+            },
+            Distribution::Hotspot {
+                keys: 100,
+                hot_fraction: 0.1,
+                hot_probability: 0.9,
+            },
+            Distribution::Sequential { keys: 100 },
+        ]
+    }
+
+    #[test]
+    fn parse_spec() {
+        assert_eq!(
+            "uniform:100".parse(),
+            Ok(Distribution::Uniform { keys: 100 })
+        );
+        assert_eq!(
+            "zipf:100:0.99".parse(),
+            Ok(Distribution::Zipf {
+                keys: 100,
+                skew: 0.99
+            })
+        );
+        assert_eq!(
+            "hotspot:100:0.1:0.9".parse(),
+            Ok(Distribution::Hotspot {
+                keys: 100,
+                hot_fraction: 0.1,
+                hot_probability: 0.9
+            })
+        );
+        assert_eq!(
+            "sequential:100".parse(),
+            Ok(Distribution::Sequential { keys: 100 })
+        );
+        for spec in [
+            "",
+            "uniform",
+            "uniform:0",
+            "zipf:100",
+            "zipf:100:x",
+            "lfu:1",
+        ] {
+            assert!(spec.parse::<Distribution>().is_err(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_keys() {
+        for distribution in distributions() {
+            let a = KeyGenerator::new(distribution.clone(), 7).take(1000);
+            let b = KeyGenerator::new(distribution, 7).take(1000);
+            assert!(a.eq(b));
+        }
+    }
+
+    #[test]
+    fn keys_within_key_space() {
+        for distribution in distributions() {
+            let keys = distribution.keys();
+            assert!(KeyGenerator::new(distribution, 7)
+                .take(1000)
+                .all(|key| key < keys));
+        }
+    }
+
+    #[test]
+    fn zipf_favors_small_keys() {
+        let distribution = Distribution::Zipf {
+            keys: 1000,
+            skew: 1.2,
+        };
+        let hot = KeyGenerator::new(distribution, 7)
+            .take(10000)
+            .filter(|&key| key < 10)
+            .count();
+        assert!(hot > 5000);
+    }
+
+    #[test]
+    fn hotspot_favors_hot_keys() {
+        let distribution = Distribution::Hotspot {
+            keys: 1000,
+            hot_fraction: 0.1,
+            hot_probability: 0.9,
+        };
+        let hot = KeyGenerator::new(distribution, 7)
+            .take(10000)
+            .filter(|&key| key < 100)
+            .count();
+        assert!((8500..9500).contains(&hot));
+    }
+
+    #[test]
+    fn sequential_wraps_around() {
+        let keys = KeyGenerator::new(Distribution::Sequential { keys: 3 }, 7)
+            .take(7)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![0, 1, 2, 0, 1, 2, 0]);
+    }
+}
@@ -0,0 +1,207 @@
+use common::{rng::Rng, Cache};
+
+use crate::workload::{Distribution, KeyGenerator};
+
+/// Zipfian constant used throughout YCSB.
+const ZIPF_SKEW: f64 = 0.99;
+
+/// Maximum number of records read by one scan.
+const MAX_SCAN_LENGTH: u64 = 100;
+
+/// Core workloads of the Yahoo! Cloud Serving Benchmark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Workload {
+    /// Update heavy: 50% reads, 50% updates, zipfian.
+    A,
+    /// Read mostly: 95% reads, 5% updates, zipfian.
+    B,
+    /// Read only: 100% reads, zipfian.
+    C,
+    /// Read latest: 95% reads, 5% inserts, reads favor recently inserted records.
+    D,
+    /// Short ranges: 95% scans, 5% inserts, zipfian scan start.
+    E,
+    /// Read-modify-write: 50% reads, 50% read-modify-writes, zipfian.
+    F,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 6] = [
+        Workload::A,
+        Workload::B,
+        Workload::C,
+        Workload::D,
+        Workload::E,
+        Workload::F,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workload::A => "a",
+            Workload::B => "b",
+            Workload::C => "c",
+            Workload::D => "d",
+            Workload::E => "e",
+            Workload::F => "f",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Read(u64),
+    Update(u64),
+    Insert(u64),
+    /// Read `len` consecutive records starting from the key.
+    Scan(u64, u64),
+    ReadModifyWrite(u64),
+}
+
+/// Stream of operations of a YCSB workload.
+/// Records `0..record_count` are assumed to be loaded before the run; inserts append new keys.
+pub struct OperationGenerator {
+    workload: Workload,
+    record_count: u64,
+    rng: Rng,
+    zipf: KeyGenerator,
+}
+
+impl OperationGenerator {
+    pub fn new(workload: Workload, record_count: u64, seed: u64) -> Self {
+        let distribution = Distribution::Zipf {
+            keys: record_count,
+            skew: ZIPF_SKEW,
+        };
+        Self {
+            workload,
+            record_count,
+            rng: Rng::new(seed),
+            zipf: KeyGenerator::new(distribution, seed.wrapping_add(1)),
+        }
+    }
+
+    /// Key of the most recently inserted record.
+    fn latest(&self) -> u64 {
+        self.record_count - 1
+    }
+
+    fn insert(&mut self) -> Operation {
+        self.record_count += 1;
+        Operation::Insert(self.latest())
+    }
+
+    pub fn next_operation(&mut self) -> Operation {
+        let p = self.rng.next_f64();
+        match self.workload {
+            Workload::A if p < 0.5 => Operation::Read(self.zipf.next_key()),
+            Workload::A => Operation::Update(self.zipf.next_key()),
+            Workload::B if p < 0.95 => Operation::Read(self.zipf.next_key()),
+            Workload::B => Operation::Update(self.zipf.next_key()),
+            Workload::C => Operation::Read(self.zipf.next_key()),
+            Workload::D if p < 0.95 => {
+                let distance = self.zipf.next_key().min(self.latest());
+                Operation::Read(self.latest() - distance)
+            }
+            Workload::D => self.insert(),
+            Workload::E if p < 0.95 => {
+                let len = 1 + self.rng.below(MAX_SCAN_LENGTH);
+                Operation::Scan(self.zipf.next_key(), len)
+            }
+            Workload::E => self.insert(),
+            Workload::F if p < 0.5 => Operation::Read(self.zipf.next_key()),
+            Workload::F => Operation::ReadModifyWrite(self.zipf.next_key()),
+        }
+    }
+}
+
+impl Iterator for OperationGenerator {
+    type Item = Operation;
+
+    fn next(&mut self) -> Option<Operation> {
+        Some(self.next_operation())
+    }
+}
+
+/// Apply `operation` to `cache` used as a look-aside cache: a read that misses loads the record
+/// by inserting it.
+/// Returns the number of reads that hit.
+pub fn execute<C: Cache<u64, u64>>(cache: &mut C, operation: Operation) -> u64 {
+    let mut read = |key| match cache.get(&key) {
+        Some(_) => 1,
+        None => {
+            cache.insert(key, key);
+            0
+        }
+    };
+    match operation {
+        Operation::Read(key) => read(key),
+        Operation::Scan(start, len) => (start..start + len).map(read).sum(),
+        Operation::Update(key) | Operation::Insert(key) => {
+            cache.insert(key, key);
+            0
+        }
+        Operation::ReadModifyWrite(key) => {
+            let hit = cache.get(&key).is_some();
+            cache.insert(key, key.wrapping_add(1));
+            hit as u64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(workload: Workload, matches: fn(&Operation) -> bool) -> usize {
+        OperationGenerator::new(workload, 1000, 7)
+            .take(10000)
+            .filter(matches)
+            .count()
+    }
+
+    #[test]
+    fn same_seed_same_operations() {
+        for workload in Workload::ALL {
+            let a = OperationGenerator::new(workload, 1000, 7).take(1000);
+            let b = OperationGenerator::new(workload, 1000, 7).take(1000);
+            assert!(a.eq(b));
+        }
+    }
+
+    #[test]
+    fn operation_mix() {
+        let reads = |op: &Operation| matches!(op, Operation::Read(_));
+        assert!((4500..5500).contains(&count(Workload::A, reads)));
+        assert!((9300..9700).contains(&count(Workload::B, reads)));
+        assert_eq!(count(Workload::C, reads), 10000);
+        assert!((300..700).contains(&count(Workload::D, |op| matches!(op, Operation::Insert(_)))));
+        assert!((9300..9700).contains(&count(Workload::E, |op| matches!(op, Operation::Scan(..)))));
+        assert!((4500..5500).contains(&count(Workload::F, |op| {
+            matches!(op, Operation::ReadModifyWrite(_))
+        })));
+    }
+
+    #[test]
+    fn read_latest_reads_recent_records() {
+        let recent = count(
+            Workload::D,
+            |op| matches!(op, Operation::Read(key) if *key >= 900),
+        );
+        assert!(recent > 5000);
+    }
+
+    #[test]
+    fn inserts_append_new_keys() {
+        let inserted = OperationGenerator::new(Workload::D, 1000, 7)
+            .take(10000)
+            .filter_map(|op| match op {
+                Operation::Insert(key) => Some(key),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            inserted,
+            (1000..1000 + inserted.len() as u64).collect::<Vec<_>>()
+        );
+    }
+}
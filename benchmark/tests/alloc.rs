@@ -0,0 +1,18 @@
+//! The counting allocator replaces the global allocator of the whole test binary, so this lives
+//! in its own binary rather than among the unit tests of the crate.
+
+use benchmark::alloc::CountingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+#[test]
+fn count_allocated_bytes() {
+    // The test harness may allocate concurrently, so only check monotonic counters.
+    let before = ALLOCATOR.snapshot();
+    let bytes = vec![0u8; 4096];
+    let after = ALLOCATOR.snapshot();
+    assert!(after.total - before.total >= bytes.len());
+    assert!(after.allocations > before.allocations);
+    assert!(after.peak >= bytes.len());
+}
@@ -0,0 +1,89 @@
+//! Every cache policy of this workspace behind one crate.
+//!
+//! Each policy is re-exported under a feature named after it, so downstream crates depend on this
+//! crate only and enable the policies they use:
+//!
+//! ```toml
+//! cache_experiment = { path = "...", default-features = false, features = ["fused_lru"] }
+//! ```
+
+pub use common::{
+    adaptive, builder, heap, instrument, replay, Cache, CacheBuilder, CacheEvent, CacheStats,
+    FrozenCache, GhostHistory, InsertError, InstrumentedCache, DEFAULT_CAPACITY,
+};
+
+#[cfg(feature = "fused_lru")]
+pub use fused_lru::FusedLru;
+#[cfg(feature = "lfu")]
+pub use lfu::Lfu;
+#[cfg(feature = "small_lru")]
+pub use small_lru::{lru::SMALL_CAPACITY_THRESHOLD, SmallLru};
+#[cfg(feature = "arena_lru")]
+pub use sync_naive_lru::ArenaLru;
+#[cfg(feature = "bytes")]
+pub use sync_naive_lru::BytesCache;
+#[cfg(feature = "sync_naive_lru")]
+pub use sync_naive_lru::{CowLru, PartitionedCache, PressureHandle, SyncNaiveLru, WeakValueLru};
+#[cfg(feature = "ttl_cache")]
+pub use ttl_cache::{Clock, ManualClock, SystemClock, TtlCache};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    fn build<C: Cache<i32, i32> + builder::FromConfig>() -> C {
+        CacheBuilder::new().capacity(2).build::<C>().unwrap()
+    }
+
+    #[cfg(feature = "sync_naive_lru")]
+    #[test]
+    fn build_sync_naive_lru() {
+        common::testsuite::eviction_order(SyncNaiveLru::new);
+        assert!(build::<SyncNaiveLru<_, _>>().is_empty());
+    }
+
+    #[cfg(feature = "arena_lru")]
+    #[test]
+    fn build_arena_lru() {
+        common::testsuite::eviction_order(ArenaLru::new);
+        assert!(build::<ArenaLru<_, _>>().is_empty());
+    }
+
+    #[cfg(feature = "fused_lru")]
+    #[test]
+    fn build_fused_lru() {
+        common::testsuite::eviction_order(FusedLru::new);
+        assert!(build::<FusedLru<_, _>>().is_empty());
+    }
+
+    #[cfg(feature = "small_lru")]
+    #[test]
+    fn build_small_lru() {
+        common::testsuite::eviction_order(SmallLru::new);
+        assert!(build::<SmallLru<_, _>>().is_empty());
+    }
+
+    #[cfg(feature = "lfu")]
+    #[test]
+    fn build_lfu() {
+        common::testsuite::eviction_order(Lfu::new);
+        assert!(build::<Lfu<_, _>>().is_empty());
+    }
+
+    #[cfg(feature = "ttl_cache")]
+    #[test]
+    fn build_ttl_cache() {
+        common::testsuite::capacity_bounds(|capacity| {
+            let mut cache = TtlCache::new(std::time::Duration::from_secs(3600));
+            cache.set_max_entries(Some(capacity));
+            cache
+        });
+        let cache = CacheBuilder::new()
+            .capacity(2)
+            .ttl(std::time::Duration::from_secs(3600))
+            .build::<TtlCache<i32, i32>>()
+            .unwrap();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,321 @@
+//! Cache wrapper tuning its capacity from the hit ratio it observes.
+//!
+//! Every `interval` lookups, `AdaptiveCache` compares the hit ratio of that window with the
+//! target: it grows the capacity by `step` when the ratio is below `target - tolerance` and shrinks
+//! it when the ratio is above `target + tolerance`, so that the cache settles on the smallest
+//! capacity reaching the target. The capacity always stays within `min..=max`.
+//!
+//! Built by `with_memory_budget`, it also keeps the elements within a number of bytes estimated
+//! by `heap_weight`: at every decision the capacity is capped to how many elements of the mean
+//! inserted weight fit in the budget, shrinking at once if needed. Only keys and values are
+//! estimated, not the bookkeeping of the inner cache.
+
+use crate::{
+    heap::{heap_weight, HeapSize},
+    Cache, CacheStats, InsertError,
+};
+use std::{hash::Hash, marker::PhantomData};
+
+/// Cache whose capacity can be changed after creation.
+pub trait Resize {
+    fn capacity(&self) -> usize;
+
+    /// Change the capacity, evicting elements if the cache holds more than new `capacity`.
+    fn set_capacity(&mut self, capacity: usize);
+}
+
+/// Parameters of `AdaptiveCache`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveConfig {
+    pub min_capacity: usize,
+    pub max_capacity: usize,
+    pub target_hit_ratio: f64,
+    /// Allowed deviation from `target_hit_ratio` before the capacity is changed.
+    pub tolerance: f64,
+    /// Number of lookups between decisions.
+    pub interval: u64,
+    /// Amount by which the capacity grows or shrinks at once.
+    pub step: usize,
+}
+
+/// Capacity change made by `AdaptiveCache`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Adjustment {
+    /// Number of lookups done when the decision was made.
+    pub lookups: u64,
+    /// Hit ratio of the window the decision was based on.
+    pub hit_ratio: f64,
+    pub from: usize,
+    pub to: usize,
+    /// Estimated bytes taken by the elements, with a memory budget.
+    pub estimated_bytes: Option<usize>,
+}
+
+/// Memory budget of `AdaptiveCache`.
+struct MemoryBudget<K, V> {
+    bytes: usize,
+    weigh: fn(&K, &V) -> usize,
+    /// Total weight of every element inserted so far.
+    inserted_bytes: usize,
+}
+
+pub struct AdaptiveCache<C, K, V> {
+    inner: C,
+    config: AdaptiveConfig,
+    stats: CacheStats,
+    window: CacheStats,
+    adjustments: Vec<Adjustment>,
+    budget: Option<MemoryBudget<K, V>>,
+    _marker: PhantomData<fn(K, V)>,
+}
+
+impl<C, K, V> AdaptiveCache<C, K, V>
+where
+    C: Cache<K, V> + Resize,
+    K: Hash + Eq,
+    V: Clone,
+{
+    /// Wrap `inner`, clamping its capacity into the bounds of `config`.
+    pub fn new(mut inner: C, config: AdaptiveConfig) -> Self {
+        assert!(
+            config.min_capacity <= config.max_capacity,
+            "min_capacity must not exceed max_capacity"
+        );
+        assert!(config.interval > 0, "interval must be positive");
+        let capacity = inner
+            .capacity()
+            .clamp(config.min_capacity, config.max_capacity);
+        inner.set_capacity(capacity);
+        Self {
+            inner,
+            config,
+            stats: CacheStats::default(),
+            window: CacheStats::default(),
+            adjustments: Vec::new(),
+            budget: None,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Counters of every access so far.
+    /// `inserts` counts every insertion attempt, including elements the inner cache rejected.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Every capacity change so far, oldest first.
+    pub fn adjustments(&self) -> &[Adjustment] {
+        &self.adjustments
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Mean weight of inserted elements, if there is a budget and anything was inserted.
+    fn mean_weight(&self) -> Option<usize> {
+        let budget = self.budget.as_ref()?;
+        let inserts = usize::try_from(self.stats.inserts)
+            .ok()
+            .filter(|&n| n > 0)?;
+        Some(budget.inserted_bytes / inserts)
+    }
+
+    fn adjust(&mut self) {
+        let hit_ratio = self.window.hit_ratio();
+        self.window = CacheStats::default();
+        let from = self.inner.capacity();
+        let mut to = if hit_ratio < self.config.target_hit_ratio - self.config.tolerance {
+            from.saturating_add(self.config.step)
+                .min(self.config.max_capacity)
+        } else if hit_ratio > self.config.target_hit_ratio + self.config.tolerance {
+            from.saturating_sub(self.config.step)
+                .max(self.config.min_capacity)
+        } else {
+            from
+        };
+        let mean_weight = self.mean_weight();
+        let fitting = self
+            .budget
+            .as_ref()
+            .zip(mean_weight)
+            .and_then(|(budget, weight)| budget.bytes.checked_div(weight));
+        if let Some(fitting) = fitting {
+            to = to.min(fitting.max(self.config.min_capacity));
+        }
+        if to != from {
+            self.inner.set_capacity(to);
+            self.adjustments.push(Adjustment {
+                lookups: self.stats.hits + self.stats.misses,
+                hit_ratio,
+                from,
+                to,
+                estimated_bytes: mean_weight.map(|weight| weight * self.inner.len()),
+            });
+        }
+    }
+}
+
+impl<C, K, V> AdaptiveCache<C, K, V>
+where
+    C: Cache<K, V> + Resize,
+    K: Hash + Eq + HeapSize,
+    V: Clone + HeapSize,
+{
+    /// Same as `new`, but also keep the elements within about `bytes` as estimated by
+    /// `heap_weight`. The budget wins over the hit ratio target, but not over `min_capacity`.
+    pub fn with_memory_budget(inner: C, config: AdaptiveConfig, bytes: usize) -> Self {
+        let mut cache = Self::new(inner, config);
+        cache.budget = Some(MemoryBudget {
+            bytes,
+            weigh: heap_weight::<K, V>,
+            inserted_bytes: 0,
+        });
+        cache
+    }
+}
+
+impl<C, K, V> AdaptiveCache<C, K, V> {
+    /// Count an insertion attempt of `key` and `value`.
+    fn count_insert(&mut self, key: &K, value: &V) {
+        self.stats.inserts += 1;
+        if let Some(budget) = self.budget.as_mut() {
+            budget.inserted_bytes = budget
+                .inserted_bytes
+                .saturating_add((budget.weigh)(key, value));
+        }
+    }
+}
+
+impl<C, K, V> Cache<K, V> for AdaptiveCache<C, K, V>
+where
+    C: Cache<K, V> + Resize,
+    K: Hash + Eq,
+    V: Clone,
+{
+    fn insert(&mut self, key: K, value: V) {
+        self.count_insert(&key, &value);
+        self.inner.insert(key, value);
+    }
+
+    fn try_insert(&mut self, key: K, value: V) -> Result<(), InsertError> {
+        self.count_insert(&key, &value);
+        self.inner.try_insert(key, value)
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let value = self.inner.get(key);
+        if value.is_some() {
+            self.stats.hits += 1;
+            self.window.hits += 1;
+        } else {
+            self.stats.misses += 1;
+            self.window.misses += 1;
+        }
+        if self.window.hits + self.window.misses == self.config.interval {
+            self.adjust();
+        }
+        value
+    }
+
+    /// Look `key` up without counting it in the hit ratio.
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        self.inner.get_quiet(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.inner.remove(key)
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ReferenceLru;
+
+    fn config() -> AdaptiveConfig {
+        AdaptiveConfig {
+            min_capacity: 10,
+            max_capacity: 100,
+            target_hit_ratio: 0.9,
+            tolerance: 0.1,
+            interval: 100,
+            step: 10,
+        }
+    }
+
+    /// Look up keys cycling through `0..keys`, inserting them on misses.
+    fn run(cache: &mut AdaptiveCache<ReferenceLru<u64, u64>, u64, u64>, keys: u64, n: u64) {
+        for i in 0..n {
+            let key = i % keys;
+            if cache.get(&key).is_none() {
+                cache.insert(key, key);
+            }
+        }
+    }
+
+    #[test]
+    fn grow_until_target() {
+        let mut cache = AdaptiveCache::new(ReferenceLru::new(10), config());
+        // A cyclic scan over 50 keys only hits once everything fits.
+        run(&mut cache, 50, 2000);
+        assert_eq!(cache.capacity(), 50);
+        let adjustments = cache.adjustments();
+        assert_eq!(adjustments.len(), 4);
+        assert_eq!((adjustments[0].from, adjustments[0].to), (10, 20));
+        assert_eq!(adjustments[0].lookups, 100);
+        assert_eq!(adjustments[0].hit_ratio, 0.0);
+    }
+
+    #[test]
+    fn shrink_down_to_min() {
+        let config = AdaptiveConfig {
+            target_hit_ratio: 0.5,
+            ..config()
+        };
+        let mut cache = AdaptiveCache::new(ReferenceLru::new(100), config);
+        // 5 keys always hit, however small the cache is.
+        run(&mut cache, 5, 2000);
+        assert_eq!(cache.capacity(), 10);
+        assert_eq!(cache.stats().misses, 5);
+    }
+
+    #[test]
+    fn grow_up_to_budget() {
+        // A `u64` key and value take 16 bytes, so 25 elements fit.
+        let mut cache = AdaptiveCache::with_memory_budget(ReferenceLru::new(10), config(), 400);
+        run(&mut cache, 50, 2000);
+        assert_eq!(cache.capacity(), 25);
+        let last = cache.adjustments().last().unwrap();
+        assert_eq!((last.from, last.to), (20, 25));
+        assert_eq!(last.estimated_bytes, Some(320));
+    }
+
+    #[test]
+    fn shrink_to_budget() {
+        let mut cache = AdaptiveCache::with_memory_budget(ReferenceLru::new(100), config(), 400);
+        // 5 keys always hit, so only the budget changes the capacity.
+        run(&mut cache, 5, 100);
+        assert_eq!(cache.adjustments().len(), 1);
+        assert_eq!(cache.capacity(), 25);
+        assert_eq!(cache.adjustments()[0].estimated_bytes, Some(80));
+    }
+
+    #[test]
+    fn clamp_initial_capacity() {
+        let cache = AdaptiveCache::<_, u64, u64>::new(ReferenceLru::new(1000), config());
+        assert_eq!(cache.capacity(), 100);
+    }
+}
@@ -0,0 +1,295 @@
+//! Fluent construction of caches.
+//!
+//! `CacheBuilder` collects options and validates them in one place, then hands a `CacheConfig`
+//! to the policy chosen by `build`:
+//!
+//! ```ignore
+//! let lru = CacheBuilder::new().capacity(1024).build::<SyncNaiveLru<u64, u64>>()?;
+//! ```
+//!
+//! Policies opt in by implementing `FromConfig`; a policy that cannot use an option does not
+//! implement `FromConfig` for it, e.g. only those hashing keys accept a custom hasher and only
+//! those with weights accept a weigher. A time to live is checked when building instead: policies
+//! without expiry reject it by `FromConfig::validate`.
+//!
+//! Stats and event listeners are not options: stats are kept by wrapping the built cache in
+//! `InstrumentedCache`, and subscribing to events returns a receiver, which `build` has no room
+//! for.
+
+use crate::DEFAULT_CAPACITY;
+use std::{collections::hash_map::RandomState, error::Error, fmt, time::Duration};
+
+/// Options of a cache, already validated by `CacheBuilder`.
+#[derive(Clone, Debug)]
+pub struct CacheConfig<S = RandomState, W = Unweighted> {
+    pub capacity: usize,
+    pub hash_builder: S,
+    pub tie_break: TieBreak,
+    /// Time after which an element expires, for policies expiring elements.
+    pub ttl: Option<Duration>,
+    pub weigher: W,
+}
+
+/// No weigher: every element weighs 1, so `capacity` is a number of elements.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Unweighted;
+
+/// Weigher `F` giving the weight of an element from its key and value.
+#[derive(Clone, Copy)]
+pub struct Weighted<F>(pub F);
+
+impl<F> fmt::Debug for Weighted<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Weighted(..)")
+    }
+}
+
+/// How a frequency-based policy chooses the victim among elements accessed equally often.
+/// Policies without access frequencies ignore this.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Evict the least recently accessed one.
+    #[default]
+    Lru,
+    /// Evict the one inserted first.
+    Fifo,
+    /// Evict one at random, drawing random numbers from `seed`.
+    Random { seed: u64 },
+}
+
+/// `DEFAULT_CAPACITY` with the default hasher.
+impl<S: Default> Default for CacheConfig<S> {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            hash_builder: S::default(),
+            tie_break: TieBreak::default(),
+            ttl: None,
+            weigher: Unweighted,
+        }
+    }
+}
+
+/// Cache which can be built from a `CacheConfig`.
+pub trait FromConfig<S = RandomState, W = Unweighted>: Sized {
+    /// Reject options the policy cannot honor. By default, a time to live is rejected, since most
+    /// policies never expire elements.
+    fn validate(config: &CacheConfig<S, W>) -> Result<(), BuildError> {
+        match config.ttl {
+            Some(_) => Err(BuildError::UnsupportedTtl),
+            None => Ok(()),
+        }
+    }
+
+    fn from_config(config: CacheConfig<S, W>) -> Self;
+}
+
+/// Reason `CacheBuilder::build` rejected the options.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// `capacity` was not given.
+    MissingCapacity,
+    /// `ttl` was zero, which would expire every element right away.
+    ZeroTtl,
+    /// The policy expires elements but `ttl` was not given.
+    MissingTtl,
+    /// `ttl` was given but the policy never expires elements.
+    UnsupportedTtl,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::MissingCapacity => write!(f, "capacity is required"),
+            BuildError::ZeroTtl => write!(f, "ttl must be positive"),
+            BuildError::MissingTtl => write!(f, "ttl is required by this policy"),
+            BuildError::UnsupportedTtl => write!(f, "this policy does not expire elements"),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+/// Builder of caches.
+#[derive(Clone, Debug)]
+pub struct CacheBuilder<S = RandomState, W = Unweighted> {
+    capacity: Option<usize>,
+    hash_builder: S,
+    tie_break: TieBreak,
+    ttl: Option<Duration>,
+    weigher: W,
+}
+
+impl CacheBuilder {
+    pub fn new() -> Self {
+        Self {
+            capacity: None,
+            hash_builder: RandomState::new(),
+            tie_break: TieBreak::default(),
+            ttl: None,
+            weigher: Unweighted,
+        }
+    }
+}
+
+impl Default for CacheBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, W> CacheBuilder<S, W> {
+    /// Maximum number of elements, or maximum total weight with a weigher.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Hash keys by `hash_builder` instead of `RandomState`.
+    pub fn hasher<T>(self, hash_builder: T) -> CacheBuilder<T, W> {
+        CacheBuilder {
+            capacity: self.capacity,
+            hash_builder,
+            tie_break: self.tie_break,
+            ttl: self.ttl,
+            weigher: self.weigher,
+        }
+    }
+
+    /// Weigh elements by `weigher(key, value)`, so that `capacity` bounds their total weight.
+    pub fn weigher<F>(self, weigher: F) -> CacheBuilder<S, Weighted<F>> {
+        CacheBuilder {
+            capacity: self.capacity,
+            hash_builder: self.hash_builder,
+            tie_break: self.tie_break,
+            ttl: self.ttl,
+            weigher: Weighted(weigher),
+        }
+    }
+
+    /// Expire elements `ttl` after they are inserted.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Choose victims among equally frequent elements by `tie_break` instead of LRU.
+    pub fn tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Validate the options and build a cache of policy `C`.
+    pub fn build<C: FromConfig<S, W>>(self) -> Result<C, BuildError> {
+        let capacity = self.capacity.ok_or(BuildError::MissingCapacity)?;
+        if self.ttl == Some(Duration::ZERO) {
+            return Err(BuildError::ZeroTtl);
+        }
+        let config = CacheConfig {
+            capacity,
+            hash_builder: self.hash_builder,
+            tie_break: self.tie_break,
+            ttl: self.ttl,
+            weigher: self.weigher,
+        };
+        C::validate(&config)?;
+        Ok(C::from_config(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::hash_map::DefaultHasher, hash::BuildHasherDefault};
+
+    struct Fixed(usize);
+
+    impl<S> FromConfig<S> for Fixed {
+        fn from_config(config: CacheConfig<S>) -> Self {
+            Self(config.capacity)
+        }
+    }
+
+    #[test]
+    fn build_with_capacity() {
+        let cache = CacheBuilder::new().capacity(16).build::<Fixed>().unwrap();
+        assert_eq!(cache.0, 16);
+    }
+
+    #[test]
+    fn capacity_is_required() {
+        let error = CacheBuilder::new().build::<Fixed>().err().unwrap();
+        assert_eq!(error, BuildError::MissingCapacity);
+    }
+
+    #[test]
+    fn default_config() {
+        let cache = Fixed::from_config(CacheConfig::<RandomState>::default());
+        assert_eq!(cache.0, DEFAULT_CAPACITY);
+    }
+
+    #[test]
+    fn hasher_keeps_other_options() {
+        let cache = CacheBuilder::new()
+            .capacity(4)
+            .hasher(BuildHasherDefault::<DefaultHasher>::default())
+            .build::<Fixed>()
+            .unwrap();
+        assert_eq!(cache.0, 4);
+    }
+
+    #[test]
+    fn reject_ttl() {
+        let builder = CacheBuilder::new().capacity(4);
+        assert_eq!(
+            builder.clone().ttl(Duration::ZERO).build::<Fixed>().err(),
+            Some(BuildError::ZeroTtl)
+        );
+        assert_eq!(
+            builder.ttl(Duration::from_secs(1)).build::<Fixed>().err(),
+            Some(BuildError::UnsupportedTtl)
+        );
+    }
+
+    #[test]
+    fn pass_weigher() {
+        struct Weight(usize);
+
+        impl<F: Fn(&str) -> usize> FromConfig<RandomState, Weighted<F>> for Weight {
+            fn from_config(config: CacheConfig<RandomState, Weighted<F>>) -> Self {
+                Self((config.weigher.0)("abc"))
+            }
+        }
+
+        let cache = CacheBuilder::new()
+            .capacity(4)
+            .weigher(|value: &str| value.len())
+            .build::<Weight>()
+            .unwrap();
+        assert_eq!(cache.0, 3);
+    }
+
+    #[test]
+    fn pass_tie_break() {
+        struct Chosen(TieBreak);
+
+        impl FromConfig for Chosen {
+            fn from_config(config: CacheConfig) -> Self {
+                Self(config.tie_break)
+            }
+        }
+
+        let tie_break = TieBreak::Random { seed: 1 };
+        let cache = CacheBuilder::new()
+            .capacity(4)
+            .tie_break(tie_break)
+            .build::<Chosen>()
+            .unwrap();
+        assert_eq!(cache.0, tie_break);
+        assert_eq!(
+            CacheBuilder::new().capacity(4).build::<Chosen>().unwrap().0,
+            TieBreak::Lru
+        );
+    }
+}
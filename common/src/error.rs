@@ -0,0 +1,35 @@
+use std::{error::Error, fmt};
+
+/// Reason `Cache::try_insert` rejected an entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InsertError {
+    /// The entry alone weighs more than the whole capacity, so it cannot be cached even after
+    /// evicting everything else.
+    TooHeavy { weight: usize, capacity: usize },
+    /// The version of the entry is not the expected one, because it has been changed or removed
+    /// since it was read.
+    VersionMismatch { expected: u64, actual: Option<u64> },
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertError::TooHeavy { weight, capacity } => write!(
+                f,
+                "entry of weight {} exceeds capacity {}",
+                weight, capacity
+            ),
+            InsertError::VersionMismatch {
+                expected,
+                actual: Some(actual),
+            } => write!(f, "expected version {}, found {}", expected, actual),
+            InsertError::VersionMismatch {
+                expected,
+                actual: None,
+            } => write!(f, "expected version {}, found no entry", expected),
+        }
+    }
+}
+
+impl Error for InsertError {}
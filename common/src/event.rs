@@ -0,0 +1,33 @@
+/// Change of the contents of a cache, reported to its subscribers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CacheEvent<K> {
+    /// The key was inserted or its value was replaced.
+    Inserted(K),
+    /// The key was evicted to make room for others.
+    Evicted(K),
+    /// The key was removed explicitly, including by `clear`.
+    Removed(K),
+    /// The key was dropped because it lived longer than its time to live.
+    Expired(K),
+}
+
+impl<K> CacheEvent<K> {
+    pub fn key(&self) -> &K {
+        match self {
+            CacheEvent::Inserted(key)
+            | CacheEvent::Evicted(key)
+            | CacheEvent::Removed(key)
+            | CacheEvent::Expired(key) => key,
+        }
+    }
+
+    /// Same event on `f(key)`.
+    pub fn map<T>(self, f: impl FnOnce(K) -> T) -> CacheEvent<T> {
+        match self {
+            CacheEvent::Inserted(key) => CacheEvent::Inserted(f(key)),
+            CacheEvent::Evicted(key) => CacheEvent::Evicted(f(key)),
+            CacheEvent::Removed(key) => CacheEvent::Removed(f(key)),
+            CacheEvent::Expired(key) => CacheEvent::Expired(f(key)),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Read-only cache built from the contents of another one, e.g. one warmed up at startup.
+/// Lookups do not promote elements and take `&self`, so this can be shared between threads by
+/// `Arc` without locking, as long as keys and values are `Send + Sync`.
+#[derive(Clone, Debug)]
+pub struct FrozenCache<K, V> {
+    map: HashMap<K, V>,
+}
+
+impl<K, V> FrozenCache<K, V>
+where
+    K: Hash + Eq,
+{
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.map.get(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Number of elements in the cache.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Elements in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.map.iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for FrozenCache<K, V>
+where
+    K: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            map: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn share_between_threads() {
+        let frozen = Arc::new((0..100).map(|i| (i, i * 2)).collect::<FrozenCache<_, _>>());
+        let handles = (0..4)
+            .map(|_| {
+                let frozen = Arc::clone(&frozen);
+                thread::spawn(move || (0..100).all(|i| frozen.get(&i) == Some(&(i * 2))))
+            })
+            .collect::<Vec<_>>();
+        assert!(handles.into_iter().all(|handle| handle.join().unwrap()));
+        assert_eq!(frozen.len(), 100);
+        assert!(!frozen.contains_key(&100));
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap, VecDeque},
+    hash::{BuildHasher, Hash},
+};
+
+/// Bounded history of keys evicted recently, remembered by hash only.
+/// This is the ghost list of policies such as 2Q and ARC, and lets admission policies favor keys
+/// which come back soon after eviction.
+///
+/// Only the `capacity` most recent records are kept. Keys are not stored, so a key whose hash
+/// collides with a recorded one is reported as recorded too.
+#[derive(Clone, Debug)]
+pub struct GhostHistory<S = RandomState> {
+    /// Hashes in order of recording, with the sequence number of each record.
+    /// A record is stale when `members` has a later one for the same hash, or none at all.
+    order: VecDeque<(u64, u64)>,
+    members: HashMap<u64, u64>,
+    capacity: usize,
+    seq: u64,
+    hash_builder: S,
+}
+
+impl GhostHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<S: BuildHasher> GhostHistory<S> {
+    pub fn with_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self {
+            order: VecDeque::with_capacity(capacity),
+            members: HashMap::with_capacity(capacity),
+            capacity,
+            seq: 0,
+            hash_builder,
+        }
+    }
+
+    /// Remember `key`, forgetting the oldest record if the history is full.
+    /// Recording a key again makes it the most recent record.
+    pub fn record<K: Hash>(&mut self, key: &K) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.order.len() == self.capacity {
+            self.pop_oldest();
+        }
+        let hash = self.hash_builder.hash_one(key);
+        self.seq += 1;
+        self.order.push_back((hash, self.seq));
+        self.members.insert(hash, self.seq);
+    }
+
+    pub fn contains<K: Hash>(&self, key: &K) -> bool {
+        self.members.contains_key(&self.hash_builder.hash_one(key))
+    }
+
+    /// Forget `key`, e.g. when it is cached again, and return whether it was recorded.
+    pub fn forget<K: Hash>(&mut self, key: &K) -> bool {
+        self.members
+            .remove(&self.hash_builder.hash_one(key))
+            .is_some()
+    }
+
+    /// Number of keys recorded.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.order.clear();
+        self.members.clear();
+    }
+
+    fn pop_oldest(&mut self) {
+        if let Some((hash, seq)) = self.order.pop_front() {
+            if self.members.get(&hash) == Some(&seq) {
+                self.members.remove(&hash);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_most_recent_records() {
+        let mut history = GhostHistory::new(2);
+        history.record(&1);
+        history.record(&2);
+        history.record(&3);
+        assert!(!history.contains(&1));
+        assert!(history.contains(&2));
+        assert!(history.contains(&3));
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn record_again_and_forget() {
+        let mut history = GhostHistory::new(2);
+        history.record(&1);
+        history.record(&2);
+        history.record(&1);
+        history.record(&3);
+        // The first record of 1 has been dropped, but not the second one.
+        assert!(history.contains(&1));
+        assert!(history.forget(&1));
+        assert!(!history.forget(&1));
+        history.record(&4);
+        assert!(history.contains(&3));
+        assert!(!history.contains(&1));
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut history = GhostHistory::new(0);
+        history.record(&1);
+        assert!(history.is_empty());
+    }
+}
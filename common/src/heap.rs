@@ -0,0 +1,104 @@
+//! Estimation of the memory taken by keys and values, for weighing elements in bytes.
+
+use std::mem;
+
+/// Number of bytes a value owns on the heap, not counting its inline size.
+/// This is an estimate: capacities count but allocator overhead does not, and data shared by
+/// `Rc` or `Arc` is not owned, so it is left out.
+pub trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! impl_heap_size_inline {
+    ($($ty:ty),*) => {
+        $(
+            impl HeapSize for $ty {
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_heap_size_inline!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    &'static str
+);
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for Box<str> {
+    fn heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        mem::size_of::<T>() + (**self).heap_size()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size()
+    }
+}
+
+/// Estimated bytes taken by an element: the inline size of its key and value plus what they own
+/// on the heap. This charges keys as well as values, which matters for string keys with small
+/// values.
+pub fn heap_weight<K: HeapSize, V: HeapSize>(key: &K, value: &V) -> usize {
+    mem::size_of::<K>() + key.heap_size() + mem::size_of::<V>() + value.heap_size()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_owned_bytes() {
+        let string = mem::size_of::<String>();
+        assert_eq!(42u64.heap_size(), 0);
+        assert_eq!(String::with_capacity(10).heap_size(), 10);
+        assert_eq!(vec![String::from("ab"); 2].heap_size(), 2 * string + 4);
+        assert_eq!(Some(Box::<str>::from("abc")).heap_size(), 3);
+        assert_eq!(
+            heap_weight(&String::from("key"), &1u32),
+            string + 3 + mem::size_of::<u32>()
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Observability for any cache without the policy knowing about it.
+//!
+//! `InstrumentedCache` wraps a `Cache` and records hits, misses and the latency of every
+//! operation it delegates. With `with_op_log`, it also keeps every operation as an `Op`, so that
+//! the same traffic can be run through another implementation later.
+
+use crate::{model::Op, Cache, CacheStats, InsertError};
+use std::{
+    hash::Hash,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+/// Latencies of one kind of operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpLatency {
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl OpLatency {
+    /// Mean latency, zero if nothing has been recorded.
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos((self.total.as_nanos() / u128::from(self.count)) as u64)
+    }
+
+    fn record(&mut self, latency: Duration) {
+        self.count += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+}
+
+/// Latencies of each kind of operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Latencies {
+    pub insert: OpLatency,
+    pub get: OpLatency,
+    pub remove: OpLatency,
+    pub clear: OpLatency,
+}
+
+pub struct InstrumentedCache<C, K, V> {
+    inner: C,
+    stats: CacheStats,
+    latencies: Latencies,
+    op_log: Option<Vec<Op<K, V>>>,
+    _marker: PhantomData<fn(K, V)>,
+}
+
+impl<C, K, V> InstrumentedCache<C, K, V>
+where
+    C: Cache<K, V>,
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            stats: CacheStats::default(),
+            latencies: Latencies::default(),
+            op_log: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Same as `new`, also logging every operation.
+    pub fn with_op_log(inner: C) -> Self {
+        Self {
+            op_log: Some(Vec::new()),
+            ..Self::new(inner)
+        }
+    }
+
+    /// Counters of every access so far.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn latencies(&self) -> Latencies {
+        self.latencies
+    }
+
+    /// Operations so far, oldest first, or `None` without logging.
+    pub fn op_log(&self) -> Option<&[Op<K, V>]> {
+        self.op_log.as_deref()
+    }
+
+    /// Take the operations logged so far, leaving the log empty.
+    pub fn take_op_log(&mut self) -> Option<Vec<Op<K, V>>> {
+        self.op_log.as_mut().map(std::mem::take)
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn log(&mut self, op: impl FnOnce() -> Op<K, V>) {
+        if let Some(op_log) = self.op_log.as_mut() {
+            op_log.push(op());
+        }
+    }
+}
+
+impl<C, K, V> Cache<K, V> for InstrumentedCache<C, K, V>
+where
+    C: Cache<K, V>,
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn insert(&mut self, key: K, value: V) {
+        self.log(|| Op::Insert(key.clone(), value.clone()));
+        let start = Instant::now();
+        self.inner.insert(key, value);
+        self.latencies.insert.record(start.elapsed());
+        self.stats.inserts += 1;
+    }
+
+    /// Only a successful insertion is logged: a rejected one leaves the inner cache unchanged,
+    /// whereas replaying it as `Op::Insert` could drop the element of `key`.
+    fn try_insert(&mut self, key: K, value: V) -> Result<(), InsertError> {
+        let op = self
+            .op_log
+            .is_some()
+            .then(|| Op::Insert(key.clone(), value.clone()));
+        let start = Instant::now();
+        let result = self.inner.try_insert(key, value);
+        self.latencies.insert.record(start.elapsed());
+        if result.is_ok() {
+            self.stats.inserts += 1;
+            if let Some(op) = op {
+                self.log(|| op);
+            }
+        }
+        result
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.log(|| Op::Get(key.clone()));
+        let start = Instant::now();
+        let value = self.inner.get(key);
+        self.latencies.get.record(start.elapsed());
+        if value.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        value
+    }
+
+    /// Look `key` up without recording it: quiet lookups leave the inner cache as it is, so
+    /// replaying the log does not need them.
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        self.inner.get_quiet(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.log(|| Op::Remove(key.clone()));
+        let start = Instant::now();
+        let value = self.inner.remove(key);
+        self.latencies.remove.record(start.elapsed());
+        value
+    }
+
+    fn clear(&mut self) {
+        self.log(|| Op::Clear);
+        let start = Instant::now();
+        self.inner.clear();
+        self.latencies.clear.record(start.elapsed());
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ReferenceLru;
+
+    crate::cache_conformance_tests!(|capacity| InstrumentedCache::new(ReferenceLru::new(capacity)));
+
+    #[test]
+    fn count_and_time_operations() {
+        let mut cache = InstrumentedCache::new(ReferenceLru::new(2));
+        cache.insert(1, 10);
+        cache.get(&1);
+        cache.get(&2);
+        cache.remove(&1);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                inserts: 1
+            }
+        );
+        let latencies = cache.latencies();
+        assert_eq!(latencies.get.count, 2);
+        assert!(latencies.get.max <= latencies.get.total);
+        assert_eq!(latencies.remove.count, 1);
+        assert_eq!(latencies.clear, OpLatency::default());
+        assert_eq!(cache.op_log(), None);
+    }
+
+    #[test]
+    fn mean_of_many_operations() {
+        let latency = OpLatency {
+            count: 1 << 32,
+            total: Duration::from_secs(1 << 33),
+            max: Duration::from_secs(3),
+        };
+        assert_eq!(latency.mean(), Duration::from_secs(2));
+        assert_eq!(OpLatency::default().mean(), Duration::ZERO);
+    }
+
+    #[test]
+    fn log_operations() {
+        let mut cache = InstrumentedCache::with_op_log(ReferenceLru::new(2));
+        cache.insert(1, 10);
+        cache.get(&1);
+        cache.clear();
+        assert_eq!(
+            cache.take_op_log(),
+            Some(vec![Op::Insert(1, 10), Op::Get(1), Op::Clear])
+        );
+        assert_eq!(cache.op_log(), Some(&[][..]));
+    }
+}
@@ -1,6 +1,32 @@
-use std::{borrow::Borrow, hash::Hash, rc::Rc};
+pub mod adaptive;
+pub mod builder;
+pub mod error;
+pub mod event;
+pub mod frozen;
+pub mod ghost;
+pub mod heap;
+pub mod instrument;
+pub mod model;
+pub mod replay;
+pub mod rng;
+pub mod testsuite;
+
+pub use builder::CacheBuilder;
+pub use error::InsertError;
+pub use event::CacheEvent;
+pub use frozen::FrozenCache;
+pub use ghost::GhostHistory;
+pub use instrument::InstrumentedCache;
+
+use std::hash::Hash;
+
+/// Capacity of caches created by `Default`, for quick tests and structs deriving `Default` where
+/// the exact size does not matter.
+pub const DEFAULT_CAPACITY: usize = 1024;
 
 /// Interface for cache.
+/// This is object-safe, so that a policy can be chosen at runtime and used through
+/// `Box<dyn Cache<K, V>>`.
 pub trait Cache<K, V>
 where
     K: Hash + Eq,
@@ -9,9 +35,84 @@ where
     /// Insert a new key-value pair.
     fn insert(&mut self, key: K, value: V);
 
-    /// Get a clone of value corresponding to `key`.
-    fn get<Q>(&mut self, key: &Q) -> Option<V>
-    where
-        Rc<K>: Borrow<Q>,
-        Q: Eq + Hash + ?Sized;
+    /// Insert a new key-value pair, failing instead of dropping it when it cannot be cached.
+    /// Policies without weights never fail.
+    fn try_insert(&mut self, key: K, value: V) -> Result<(), InsertError> {
+        self.insert(key, value);
+        Ok(())
+    }
+
+    /// Get a clone of value corresponding to `key`, counting as an access: LRU policies make it
+    /// most recently accessed and frequency-based ones count it.
+    fn get(&mut self, key: &K) -> Option<V>;
+
+    /// Get a clone of value corresponding to `key` without counting as an access, so that
+    /// inspecting the cache leaves the eviction order as it is.
+    fn get_quiet(&self, key: &K) -> Option<V>;
+
+    /// Remove an element corresponding to `key` and return its value.
+    fn remove(&mut self, key: &K) -> Option<V>;
+
+    /// Remove all elements.
+    fn clear(&mut self);
+
+    /// Number of elements in the cache.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, V, C> Cache<K, V> for Box<C>
+where
+    K: Hash + Eq,
+    V: Clone,
+    C: Cache<K, V> + ?Sized,
+{
+    fn insert(&mut self, key: K, value: V) {
+        (**self).insert(key, value)
+    }
+
+    fn try_insert(&mut self, key: K, value: V) -> Result<(), InsertError> {
+        (**self).try_insert(key, value)
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        (**self).get(key)
+    }
+
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        (**self).get_quiet(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        (**self).remove(key)
+    }
+
+    fn clear(&mut self) {
+        (**self).clear()
+    }
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+}
+
+/// Counters of accesses to a cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub inserts: u64,
+}
+
+impl CacheStats {
+    pub fn hit_ratio(&self) -> f64 {
+        let accesses = self.hits + self.misses;
+        if accesses == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / accesses as f64
+    }
 }
@@ -0,0 +1,210 @@
+//! Reference LRU cache to check other implementations against.
+//!
+//! `ReferenceLru` keeps its elements in a `VecDeque` and looks keys up linearly, which is too slow
+//! for real use but simple enough to be obviously correct. `check_against_reference` runs a
+//! sequence of operations through both a cache and the reference and panics at the first
+//! operation whose outcome differs. `check_differential` does the same against another
+//! implementation instead of the reference, and compares final contents as well. With the
+//! `proptest` feature, `operations` generates such sequences, so that a failure is shrunk to a
+//! minimal one.
+
+use crate::{adaptive::Resize, Cache};
+use std::{collections::VecDeque, fmt::Debug, hash::Hash};
+
+/// Operation on a `Cache`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Op<K, V> {
+    Insert(K, V),
+    Get(K),
+    Remove(K),
+    Clear,
+}
+
+/// LRU cache holding its elements in order from least to most recently accessed.
+pub struct ReferenceLru<K, V> {
+    entries: VecDeque<(K, V)>,
+    capacity: usize,
+}
+
+impl<K, V> ReferenceLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Keys in order from least to most recently accessed.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    fn take(&mut self, key: &K) -> Option<(K, V)> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        self.entries.remove(index)
+    }
+}
+
+impl<K, V> Cache<K, V> for ReferenceLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    fn insert(&mut self, key: K, value: V) {
+        if self.take(&key).is_none() && self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        if self.capacity > 0 {
+            self.entries.push_back((key, value));
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let entry = self.take(key)?;
+        let value = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(value)
+    }
+
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.take(key).map(|(_, value)| value)
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl<K, V> Resize for ReferenceLru<K, V> {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.entries.len().saturating_sub(capacity);
+        self.entries.drain(..excess);
+    }
+}
+
+/// Run `ops` through `cache` and a `ReferenceLru` of `capacity`, checking that every operation
+/// returns the same and leaves the same number of elements.
+/// Finally every remaining key is looked up in recency order, which also checks the order.
+pub fn check_against_reference<C, K, V>(mut cache: C, capacity: usize, ops: &[Op<K, V>])
+where
+    C: Cache<K, V>,
+    K: Hash + Eq + Clone + Debug,
+    V: Clone + PartialEq + Debug,
+{
+    let mut reference = ReferenceLru::new(capacity);
+    for (step, op) in ops.iter().enumerate() {
+        let (actual, expected) = match op {
+            Op::Insert(key, value) => {
+                cache.insert(key.clone(), value.clone());
+                reference.insert(key.clone(), value.clone());
+                (None, None)
+            }
+            Op::Get(key) => (cache.get(key), reference.get(key)),
+            Op::Remove(key) => (cache.remove(key), reference.remove(key)),
+            Op::Clear => {
+                cache.clear();
+                reference.clear();
+                (None, None)
+            }
+        };
+        assert_eq!(actual, expected, "step {}: {:?}", step, op);
+        assert_eq!(cache.len(), reference.len(), "step {}: {:?}", step, op);
+    }
+    let keys = reference.keys().cloned().collect::<Vec<_>>();
+    for key in keys {
+        assert_eq!(cache.get(&key), reference.get(&key), "key {:?}", key);
+    }
+}
+
+/// Run `ops` through `naive` and `optimized`, checking that every operation returns the same and
+/// that both end up with the same contents in the same order.
+/// This guards a rewrite of a cache against drifting from the implementation it replaces.
+pub fn check_differential<A, B, K, V>(mut naive: A, mut optimized: B, ops: &[Op<K, V>])
+where
+    A: Cache<K, V> + IntoIterator<Item = (K, V)>,
+    B: Cache<K, V> + IntoIterator<Item = (K, V)>,
+    K: Hash + Eq + Clone + Debug,
+    V: Clone + PartialEq + Debug,
+{
+    for (step, op) in ops.iter().enumerate() {
+        let (expected, actual) = match op {
+            Op::Insert(key, value) => {
+                naive.insert(key.clone(), value.clone());
+                optimized.insert(key.clone(), value.clone());
+                (None, None)
+            }
+            Op::Get(key) => (naive.get(key), optimized.get(key)),
+            Op::Remove(key) => (naive.remove(key), optimized.remove(key)),
+            Op::Clear => {
+                naive.clear();
+                optimized.clear();
+                (None, None)
+            }
+        };
+        assert_eq!(actual, expected, "step {}: {:?}", step, op);
+        assert_eq!(optimized.len(), naive.len(), "step {}: {:?}", step, op);
+    }
+    assert_eq!(
+        optimized.into_iter().collect::<Vec<_>>(),
+        naive.into_iter().collect::<Vec<_>>(),
+        "Final contents differ"
+    );
+}
+
+/// Sequences of operations on few keys, so that hits, updates and evictions are frequent.
+#[cfg(feature = "proptest")]
+pub fn operations(keys: u8) -> impl proptest::strategy::Strategy<Value = Vec<Op<u8, u32>>> {
+    use proptest::prelude::*;
+
+    let op = prop_oneof![
+        4 => (0..keys, any::<u32>()).prop_map(|(key, value)| Op::Insert(key, value)),
+        4 => (0..keys).prop_map(Op::Get),
+        2 => (0..keys).prop_map(Op::Remove),
+        1 => Just(Op::Clear),
+    ];
+    proptest::collection::vec(op, 0..200)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::cache_conformance_tests!(ReferenceLru::new);
+
+    #[test]
+    fn keys_in_recency_order() {
+        let mut lru = ReferenceLru::new(3);
+        lru.insert(1, ());
+        lru.insert(2, ());
+        lru.insert(3, ());
+        lru.get(&1);
+        assert_eq!(lru.keys().copied().collect::<Vec<_>>(), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn boxed_dyn_cache() {
+        crate::testsuite::eviction_order(|capacity| {
+            Box::new(ReferenceLru::new(capacity)) as Box<dyn Cache<i32, i32>>
+        });
+    }
+}
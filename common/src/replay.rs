@@ -0,0 +1,170 @@
+//! Replay of logged operations, e.g. captured by `InstrumentedCache::with_op_log`.
+//!
+//! `replay` runs a log through any cache and returns whether each lookup hit. Stored as a
+//! baseline by `format_baseline`, this guards a refactor of a policy: `check_baseline` panics at
+//! the first lookup whose outcome differs from the one recorded before the change.
+//!
+//! Logs can be saved as text with one operation per line, written by `Display` and read back by
+//! `FromStr`:
+//!
+//! ```text
+//! insert 1 10
+//! get 1
+//! remove 1
+//! clear
+//! ```
+
+use crate::{model::Op, Cache};
+use std::{error::Error, fmt, hash::Hash, str::FromStr};
+
+impl<K: fmt::Display, V: fmt::Display> fmt::Display for Op<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::Insert(key, value) => write!(f, "insert {} {}", key, value),
+            Op::Get(key) => write!(f, "get {}", key),
+            Op::Remove(key) => write!(f, "remove {}", key),
+            Op::Clear => write!(f, "clear"),
+        }
+    }
+}
+
+/// Line which is not an operation written by `Display` of `Op`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseOpError {
+    line: String,
+}
+
+impl fmt::Display for ParseOpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid operation: {:?}", self.line)
+    }
+}
+
+impl Error for ParseOpError {}
+
+impl<K: FromStr, V: FromStr> FromStr for Op<K, V> {
+    type Err = ParseOpError;
+
+    fn from_str(line: &str) -> Result<Self, ParseOpError> {
+        let error = || ParseOpError {
+            line: line.to_string(),
+        };
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let key = |token: &str| token.parse().map_err(|_| error());
+        match tokens[..] {
+            ["insert", k, v] => Ok(Op::Insert(key(k)?, v.parse().map_err(|_| error())?)),
+            ["get", k] => Ok(Op::Get(key(k)?)),
+            ["remove", k] => Ok(Op::Remove(key(k)?)),
+            ["clear"] => Ok(Op::Clear),
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Run `log` through `cache` and return whether each `Op::Get` hit, in order.
+pub fn replay<C, K, V>(log: &[Op<K, V>], cache: &mut C) -> Vec<bool>
+where
+    C: Cache<K, V> + ?Sized,
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    let mut hits = Vec::new();
+    for op in log {
+        match op {
+            Op::Insert(key, value) => cache.insert(key.clone(), value.clone()),
+            Op::Get(key) => hits.push(cache.get(key).is_some()),
+            Op::Remove(key) => {
+                cache.remove(key);
+            }
+            Op::Clear => cache.clear(),
+        }
+    }
+    hits
+}
+
+/// Outcomes of lookups as a line of `H` for hits and `M` for misses, to be stored as a baseline.
+pub fn format_baseline(hits: &[bool]) -> String {
+    hits.iter()
+        .map(|&hit| if hit { 'H' } else { 'M' })
+        .collect()
+}
+
+/// Replay `log` through `cache`, checking that lookups hit and miss as recorded in `baseline`,
+/// a string written by `format_baseline`. Whitespace in `baseline` is ignored, so it can be
+/// wrapped.
+pub fn check_baseline<C, K, V>(log: &[Op<K, V>], cache: &mut C, baseline: &str)
+where
+    C: Cache<K, V> + ?Sized,
+    K: Hash + Eq + Clone + fmt::Debug,
+    V: Clone,
+{
+    let expected = baseline
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            'H' => true,
+            'M' => false,
+            _ => panic!("Baseline must consist of H and M, found {:?}", c),
+        })
+        .collect::<Vec<_>>();
+    let actual = replay(log, cache);
+    let keys = log.iter().filter_map(|op| match op {
+        Op::Get(key) => Some(key),
+        _ => None,
+    });
+    for (lookup, ((actual, expected), key)) in actual.iter().zip(&expected).zip(keys).enumerate() {
+        assert_eq!(actual, expected, "lookup {} of key {:?}", lookup, key);
+    }
+    assert_eq!(actual.len(), expected.len(), "Number of lookups differs");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{instrument::InstrumentedCache, model::ReferenceLru};
+
+    fn log() -> Vec<Op<u64, u64>> {
+        vec![
+            Op::Insert(1, 10),
+            Op::Insert(2, 20),
+            Op::Get(1),
+            Op::Insert(3, 30),
+            Op::Get(2),
+            Op::Remove(1),
+            Op::Get(1),
+            Op::Get(3),
+            Op::Clear,
+            Op::Get(3),
+        ]
+    }
+
+    #[test]
+    fn replay_recorded_log() {
+        let mut recorder = InstrumentedCache::with_op_log(ReferenceLru::new(2));
+        let expected = replay(&log(), &mut recorder);
+        assert_eq!(format_baseline(&expected), "HMMHM");
+        let recorded = recorder.take_op_log().unwrap();
+        assert_eq!(recorded, log());
+        check_baseline(&recorded, &mut ReferenceLru::new(2), "HMM HM");
+    }
+
+    #[test]
+    #[should_panic(expected = "lookup 1 of key 2")]
+    fn detect_divergence() {
+        check_baseline(&log(), &mut ReferenceLru::new(3), "HMMHM");
+    }
+
+    #[test]
+    fn round_trip_text() {
+        let text = log().iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(text[0], "insert 1 10");
+        let parsed = text
+            .iter()
+            .map(|line| line.parse())
+            .collect::<Result<Vec<Op<u64, u64>>, _>>()
+            .unwrap();
+        assert_eq!(parsed, log());
+        assert!("get".parse::<Op<u64, u64>>().is_err());
+        assert!("get x".parse::<Op<u64, u64>>().is_err());
+    }
+}
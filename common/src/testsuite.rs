@@ -0,0 +1,159 @@
+//! Conformance tests every `Cache` implementation is expected to pass.
+//!
+//! Each function takes a constructor building a cache from its capacity and panics when the cache
+//! built by it does not behave as an LRU cache. `cache_conformance_tests!` expands to one `#[test]`
+//! per function, so an implementation can run the whole suite from its test module:
+//!
+//! ```ignore
+//! #[cfg(test)]
+//! mod tests {
+//!     common::cache_conformance_tests!(MyLru::new);
+//! }
+//! ```
+
+use crate::Cache;
+
+/// The least-recently accessed element is evicted first, and `get` counts as an access.
+pub fn eviction_order<C: Cache<i32, i32>>(make: impl Fn(usize) -> C) {
+    let mut cache = make(3);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(3, 30);
+    assert_eq!(cache.get(&1), Some(10));
+    cache.insert(4, 40);
+    assert_eq!(cache.get(&2), None);
+    cache.insert(5, 50);
+    assert_eq!(cache.get(&3), None);
+    assert_eq!(cache.get(&1), Some(10));
+    assert_eq!(cache.get(&4), Some(40));
+    assert_eq!(cache.get(&5), Some(50));
+}
+
+/// Inserting an existing key replaces its value, keeps the length and makes it most recent.
+pub fn update_semantics<C: Cache<i32, i32>>(make: impl Fn(usize) -> C) {
+    let mut cache = make(2);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(1, 11);
+    assert_eq!(cache.len(), 2);
+    cache.insert(3, 30);
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.get(&1), Some(11));
+    assert_eq!(cache.get(&3), Some(30));
+}
+
+/// The cache never holds more than `capacity` elements, including when the capacity is zero.
+pub fn capacity_bounds<C: Cache<i32, i32>>(make: impl Fn(usize) -> C) {
+    for capacity in [0, 1, 2, 7] {
+        let mut cache = make(capacity);
+        assert!(cache.is_empty());
+        for key in 0..20 {
+            cache.insert(key, key);
+            assert!(cache.len() <= capacity, "capacity {}", capacity);
+        }
+        assert_eq!(cache.len(), capacity);
+        for key in 20 - capacity as i32..20 {
+            assert_eq!(cache.get(&key), Some(key), "capacity {}", capacity);
+        }
+    }
+}
+
+/// Looking up an absent key misses and changes nothing.
+pub fn get_miss<C: Cache<i32, i32>>(make: impl Fn(usize) -> C) {
+    let mut cache = make(2);
+    assert_eq!(cache.get(&1), None);
+    cache.insert(1, 10);
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.len(), 1);
+}
+
+/// `remove` returns the value of a present key only, and frees its room.
+pub fn remove<C: Cache<i32, i32>>(make: impl Fn(usize) -> C) {
+    let mut cache = make(3);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(3, 30);
+    assert_eq!(cache.remove(&2), Some(20));
+    assert_eq!(cache.remove(&2), None);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&2), None);
+
+    // The freed room is used before anything is evicted.
+    cache.insert(4, 40);
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.get(&1), Some(10));
+    assert_eq!(cache.get(&3), Some(30));
+    assert_eq!(cache.get(&4), Some(40));
+
+    // Removing the least and most recent elements keeps the order of the others. 5 and 6 are
+    // hit as often as 3 was, so that policies counting accesses evict 3 as well.
+    assert_eq!(cache.remove(&1), Some(10));
+    assert_eq!(cache.remove(&4), Some(40));
+    cache.insert(5, 50);
+    cache.insert(6, 60);
+    assert_eq!(cache.get(&5), Some(50));
+    assert_eq!(cache.get(&6), Some(60));
+    cache.insert(7, 70);
+    assert_eq!(cache.get(&3), None);
+    assert_eq!(cache.get(&5), Some(50));
+}
+
+/// `clear` empties the cache, and it can be filled up again.
+pub fn clear<C: Cache<i32, i32>>(make: impl Fn(usize) -> C) {
+    let mut cache = make(2);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(cache.get(&1), None);
+    cache.insert(3, 30);
+    cache.insert(4, 40);
+    cache.insert(5, 50);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&3), None);
+    assert_eq!(cache.get(&4), Some(40));
+}
+
+/// `get_quiet` returns the same value as `get` but does not count as an access.
+pub fn get_quiet<C: Cache<i32, i32>>(make: impl Fn(usize) -> C) {
+    let mut cache = make(3);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(3, 30);
+    assert_eq!(cache.get_quiet(&1), Some(10));
+    assert_eq!(cache.get_quiet(&4), None);
+    cache.insert(4, 40);
+    assert_eq!(cache.get_quiet(&1), None);
+    assert_eq!(cache.get_quiet(&2), Some(20));
+    assert_eq!(cache.len(), 3);
+}
+
+/// Expand to a `#[test]` for each function of `common::testsuite`, building caches by `$make`.
+#[macro_export]
+macro_rules! cache_conformance_tests {
+    ($make:expr) => {
+        $crate::cache_conformance_tests!(
+            $make;
+            eviction_order,
+            update_semantics,
+            capacity_bounds,
+            get_miss,
+            remove,
+            clear,
+            get_quiet
+        );
+    };
+    ($make:expr; $($name:ident),*) => {
+        mod conformance {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $(
+                #[test]
+                fn $name() {
+                    $crate::testsuite::$name($make);
+                }
+            )*
+        }
+    };
+}
@@ -0,0 +1,33 @@
+use crate::lru::{Entry, FusedLru, NIL};
+
+pub struct IntoIter<K, V> {
+    slots: Vec<Option<Entry<K, V>>>,
+    current: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current == NIL {
+            return None;
+        }
+        let entry = self.slots[self.current]
+            .take()
+            .expect("Each slot is visited once");
+        self.current = entry.next;
+        Some((entry.key, entry.value))
+    }
+}
+
+impl<K, V, S> IntoIterator for FusedLru<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter {
+            slots: self.slots,
+            current: self.tail,
+        }
+    }
+}
@@ -0,0 +1,448 @@
+use common::{
+    builder::{CacheConfig, FromConfig},
+    Cache,
+};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash},
+};
+
+/// Index standing for "no slot", used in place of `None` for links.
+pub(crate) const NIL: usize = usize::MAX;
+
+pub(crate) struct Entry<K, V> {
+    pub(crate) next: usize,
+    pub(crate) prev: usize,
+    hash: u64,
+    pub(crate) key: K,
+    pub(crate) value: V,
+}
+
+/// LRU cache implemented by an open-addressing hash table whose slots themselves form the
+/// doubly-linked recency list.
+/// As in `SyncNaiveLru`, `next` points to more recently accessed element and `prev` to less
+/// recently accessed one, but there is no separate node structure; each link is an index of a slot.
+///
+/// Collisions are resolved by linear probing and removal is done by backward-shift deletion, so
+/// the table never contains tombstones. Links of an entry moving between slots are fixed up at the
+/// same time.
+pub struct FusedLru<K, V, S = RandomState> {
+    pub(crate) slots: Vec<Option<Entry<K, V>>>,
+    head: usize,
+    pub(crate) tail: usize,
+    len: usize,
+    capacity: usize,
+    hash_builder: S,
+}
+
+impl<K, V> FusedLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> FusedLru<K, V, S>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+{
+    /// Create a cache hashing keys by `hash_builder`.
+    /// The table is kept at most half full to keep probe sequences short.
+    pub fn with_hasher(capacity: usize, hash_builder: S) -> Self {
+        let table_size = (capacity * 2).next_power_of_two();
+        Self {
+            slots: (0..table_size).map(|_| None).collect(),
+            head: NIL,
+            tail: NIL,
+            len: 0,
+            capacity,
+            hash_builder,
+        }
+    }
+
+    fn mask(&self) -> usize {
+        self.slots.len() - 1
+    }
+
+    fn entry(&self, index: usize) -> &Entry<K, V> {
+        self.slots[index].as_ref().expect("Slot must be occupied")
+    }
+
+    fn entry_mut(&mut self, index: usize) -> &mut Entry<K, V> {
+        self.slots[index].as_mut().expect("Slot must be occupied")
+    }
+
+    /// Find the slot holding `key`.
+    fn find(&self, hash: u64, key: &K) -> Option<usize> {
+        let mask = self.mask();
+        let mut index = hash as usize & mask;
+        loop {
+            match self.slots[index].as_ref() {
+                None => return None,
+                Some(entry) if entry.hash == hash && &entry.key == key => return Some(index),
+                Some(_) => index = (index + 1) & mask,
+            }
+        }
+    }
+
+    /// Attach the entry at `index` to the head of linked list.
+    fn attach(&mut self, index: usize) {
+        let head = self.head;
+        let entry = self.entry_mut(index);
+        entry.prev = head;
+        entry.next = NIL;
+        if head != NIL {
+            self.entry_mut(head).next = index;
+        } else {
+            self.tail = index;
+        }
+        self.head = index;
+    }
+
+    fn detach(&mut self, index: usize) {
+        let (prev, next) = {
+            let entry = self.entry(index);
+            (entry.prev, entry.next)
+        };
+        if prev != NIL {
+            self.entry_mut(prev).next = next;
+        } else {
+            // The entry is tail element.
+            self.tail = next;
+        }
+        if next != NIL {
+            self.entry_mut(next).prev = prev;
+        } else {
+            // The entry is head element.
+            self.head = prev;
+        }
+    }
+
+    /// Point links referring to the entry at `from` to `to`, where the entry has been moved.
+    fn relink(&mut self, from: usize, to: usize) {
+        let (prev, next) = {
+            let entry = self.entry(to);
+            (entry.prev, entry.next)
+        };
+        if prev != NIL {
+            self.entry_mut(prev).next = to;
+        }
+        if self.tail == from {
+            self.tail = to;
+        }
+        if next != NIL {
+            self.entry_mut(next).prev = to;
+        }
+        if self.head == from {
+            self.head = to;
+        }
+    }
+
+    /// Remove the entry at `index`, which must already be detached from the list, and shift
+    /// following entries of the probe sequence back into the hole.
+    fn remove_slot(&mut self, index: usize) -> Entry<K, V> {
+        let mask = self.mask();
+        let removed = self.slots[index].take().expect("Slot must be occupied");
+        self.len -= 1;
+
+        let mut hole = index;
+        let mut current = (index + 1) & mask;
+        while let Some(entry) = self.slots[current].as_ref() {
+            let home = entry.hash as usize & mask;
+            // The entry can fill the hole unless its home lies cyclically in (hole, current].
+            if (current.wrapping_sub(home) & mask) >= (current.wrapping_sub(hole) & mask) {
+                self.slots[hole] = self.slots[current].take();
+                self.relink(current, hole);
+                hole = current;
+            }
+            current = (current + 1) & mask;
+        }
+        removed
+    }
+}
+
+impl<K, V, S> FromConfig<S> for FusedLru<K, V, S>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+{
+    fn from_config(config: CacheConfig<S>) -> Self {
+        Self::with_hasher(config.capacity, config.hash_builder)
+    }
+}
+
+impl<K, V, S> Cache<K, V> for FusedLru<K, V, S>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+{
+    /// Insert a new key-value pair.
+    /// If the number of existing elements is `capacity`, remove least-recently accessed one.
+    fn insert(&mut self, key: K, value: V) {
+        let hash = self.hash_builder.hash_one(&key);
+        if let Some(index) = self.find(hash, &key) {
+            self.entry_mut(index).value = value;
+            self.detach(index);
+            self.attach(index);
+            return;
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+        if self.len == self.capacity {
+            let tail = self.tail;
+            self.detach(tail);
+            self.remove_slot(tail);
+        }
+
+        let mask = self.mask();
+        let mut index = hash as usize & mask;
+        while self.slots[index].is_some() {
+            index = (index + 1) & mask;
+        }
+        self.slots[index] = Some(Entry {
+            next: NIL,
+            prev: NIL,
+            hash,
+            key,
+            value,
+        });
+        self.len += 1;
+        self.attach(index);
+    }
+
+    /// Get clone of a value corresponding to `key`.
+    /// This moves accessed element to head of the list.
+    fn get(&mut self, key: &K) -> Option<V> {
+        let hash = self.hash_builder.hash_one(key);
+        let index = self.find(hash, key)?;
+        self.detach(index);
+        self.attach(index);
+        Some(self.entry(index).value.clone())
+    }
+
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        let hash = self.hash_builder.hash_one(key);
+        let index = self.find(hash, key)?;
+        Some(self.entry(index).value.clone())
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let hash = self.hash_builder.hash_one(key);
+        let index = self.find(hash, key)?;
+        self.detach(index);
+        Some(self.remove_slot(index).value)
+    }
+
+    fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.head = NIL;
+        self.tail = NIL;
+        self.len = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Caches are equal when they have the same capacity and the same elements in the same recency
+/// order, however the elements are laid out in the table.
+impl<K: PartialEq, V: PartialEq, S> PartialEq for FusedLru<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.capacity != other.capacity || self.len != other.len {
+            return false;
+        }
+        let (mut index, mut other_index) = (self.tail, other.tail);
+        while index != NIL && other_index != NIL {
+            let entry = self.slots[index].as_ref().expect("Slot must be occupied");
+            let other_entry = other.slots[other_index]
+                .as_ref()
+                .expect("Slot must be occupied");
+            if entry.key != other_entry.key || entry.value != other_entry.value {
+                return false;
+            }
+            (index, other_index) = (entry.next, other_entry.next);
+        }
+        true
+    }
+}
+
+impl<K: Eq, V: Eq, S> Eq for FusedLru<K, V, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::CacheBuilder;
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    common::cache_conformance_tests!(FusedLru::new);
+
+    proptest::proptest! {
+        #[test]
+        fn matches_reference_model(capacity in 0usize..8, ops in common::model::operations(16)) {
+            common::model::check_against_reference(FusedLru::new(capacity), capacity, &ops);
+        }
+
+        #[test]
+        fn matches_sync_naive_lru(capacity in 0usize..8, ops in common::model::operations(16)) {
+            common::model::check_differential(
+                sync_naive_lru::SyncNaiveLru::new(capacity),
+                FusedLru::new(capacity),
+                &ops,
+            );
+        }
+
+        #[test]
+        fn matches_reference_model_with_collisions(
+            capacity in 0usize..8,
+            ops in common::model::operations(16),
+        ) {
+            let lru = FusedLru::with_hasher(capacity, BuildHasherDefault::<CollidingHasher>::default());
+            common::model::check_against_reference(lru, capacity, &ops);
+        }
+    }
+
+    /// Hasher sending every key to the same slot to exercise probing and backward shifts.
+    #[derive(Default)]
+    struct CollidingHasher;
+
+    impl Hasher for CollidingHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    fn setup_lru_with_capacity_3() -> FusedLru<i32, i32> {
+        let mut lru = FusedLru::new(3);
+        [(1, 2), (3, 4), (5, 6)]
+            .iter()
+            .for_each(|kv| lru.insert(kv.0, kv.1));
+        lru
+    }
+
+    #[test]
+    fn just_insert() {
+        let lru = setup_lru_with_capacity_3();
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(1, 2), (3, 4), (5, 6)]
+        );
+    }
+
+    #[test]
+    fn exceeding_insert() {
+        let mut lru = FusedLru::new(3);
+        let expected = [(1, 2), (3, 4), (5, 6), (7, 8)];
+        expected.iter().for_each(|kv| lru.insert(kv.0, kv.1));
+
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(3, 4), (5, 6), (7, 8)]
+        );
+    }
+
+    #[test]
+    fn get_reorders_entry() {
+        let mut lru = setup_lru_with_capacity_3();
+        assert_eq!(lru.get(&3), Some(4));
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(1, 2), (5, 6), (3, 4)]
+        );
+    }
+
+    #[test]
+    fn insert_existing_key_updates_value() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.insert(1, 10);
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(3, 4), (5, 6), (1, 10)]
+        );
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut lru = FusedLru::new(0);
+        lru.insert(1, 2);
+        assert_eq!(lru.get(&1), None);
+    }
+
+    #[test]
+    fn backward_shift_keeps_links() {
+        let mut lru = FusedLru::with_hasher(4, BuildHasherDefault::<CollidingHasher>::default());
+        (0..4).for_each(|i| lru.insert(i, i));
+        assert_eq!(lru.get(&0), Some(0));
+        // Evicts 1, which shifts 2 and 3 back over its slot.
+        lru.insert(4, 4);
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(lru.get(&3), Some(3));
+        lru.insert(5, 5);
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(0, 0), (4, 4), (3, 3), (5, 5)]
+        );
+    }
+
+    #[test]
+    fn many_insertions_keep_most_recent() {
+        let mut lru = FusedLru::new(8);
+        (0..1000).for_each(|i| lru.insert(i * 7919 % 1000, i));
+        assert_eq!(
+            lru.into_iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            (992..1000).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn build_from_builder() {
+        let mut lru = CacheBuilder::new()
+            .capacity(1)
+            .build::<FusedLru<i32, i32>>()
+            .unwrap();
+        lru.insert(1, 2);
+        lru.insert(3, 4);
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(lru.get(&3), Some(4));
+    }
+
+    #[test]
+    fn build_with_hasher() {
+        let mut lru = CacheBuilder::new()
+            .capacity(2)
+            .hasher(BuildHasherDefault::<CollidingHasher>::default())
+            .build::<FusedLru<i32, i32, _>>()
+            .unwrap();
+        (0..3).for_each(|i| lru.insert(i, i));
+        assert_eq!(lru.into_iter().collect::<Vec<_>>(), vec![(1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn equal_in_recency_order() {
+        let mut lru = FusedLru::new(3);
+        let mut other = FusedLru::new(3);
+        for key in [1, 2, 3] {
+            lru.insert(key, key);
+            other.insert(key, key);
+        }
+        assert!(lru == other);
+        lru.get(&1);
+        assert!(lru != other);
+        other.get(&1);
+        assert!(lru == other);
+        other.insert(3, 30);
+        assert!(lru != other);
+    }
+}
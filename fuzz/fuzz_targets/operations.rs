@@ -0,0 +1,50 @@
+//! Run arbitrary sequences of operations through `SyncNaiveLru`, checking its invariants after
+//! every operation.
+//!
+//! ```sh
+//! cargo +nightly fuzz run operations
+//! ```
+#![no_main]
+
+use arbitrary::Arbitrary;
+use common::Cache;
+use libfuzzer_sys::fuzz_target;
+use sync_naive_lru::SyncNaiveLru;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Insert(u8, u8),
+    Get(u8),
+    Remove(u8),
+    Clear,
+    SetCapacity(u8),
+    /// Insertion point in 255ths of the list.
+    SetMidpointInsertion(Option<u8>),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    capacity: u8,
+    ops: Vec<Op>,
+}
+
+fuzz_target!(|input: Input| {
+    let mut lru = SyncNaiveLru::new(input.capacity as usize);
+    for op in input.ops {
+        match op {
+            Op::Insert(key, value) => lru.insert(key, value),
+            Op::Get(key) => {
+                lru.get(&key);
+            }
+            Op::Remove(key) => {
+                lru.remove(&key);
+            }
+            Op::Clear => lru.clear(),
+            Op::SetCapacity(capacity) => lru.set_capacity(capacity as usize),
+            Op::SetMidpointInsertion(point) => {
+                lru.set_midpoint_insertion(point.map(|point| point as f64 / 255.0))
+            }
+        }
+        lru.validate();
+    }
+});
@@ -0,0 +1,108 @@
+use crate::wire::Invalidation;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    io::{self, ErrorKind},
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket},
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+/// Largest datagram accepted, which bounds the size of encoded keys.
+const MAX_DATAGRAM: usize = 1500;
+
+/// Endpoint sending and receiving invalidations of one cache instance.
+pub struct Broadcaster {
+    socket: UdpSocket,
+    targets: Vec<SocketAddr>,
+    /// Id put in sent messages, so that an instance ignores its own ones looped back by multicast.
+    id: u64,
+}
+
+impl Broadcaster {
+    /// Bind `addr` to send invalidations to peers added by `add_peer`.
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        Self::new(UdpSocket::bind(addr)?, Vec::new())
+    }
+
+    /// Join the multicast `group` on `port` through `interface` and send invalidations to the
+    /// group. `Ipv4Addr::UNSPECIFIED` lets the system choose the interface.
+    /// Every member binds the same port, so several instances can join on one host.
+    pub fn join_multicast(group: Ipv4Addr, port: u16, interface: Ipv4Addr) -> io::Result<Self> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        socket.set_reuse_address(true)?;
+        socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port).into())?;
+        socket.join_multicast_v4(&group, &interface)?;
+        socket.set_multicast_if_v4(&interface)?;
+        socket.set_multicast_loop_v4(true)?;
+        Self::new(socket.into(), vec![SocketAddrV4::new(group, port).into()])
+    }
+
+    fn new(socket: UdpSocket, targets: Vec<SocketAddr>) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            targets,
+            id: random_id(),
+        })
+    }
+
+    /// Send invalidations to `peer` as well.
+    pub fn add_peer(&mut self, peer: SocketAddr) {
+        self.targets.push(peer);
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Tell every peer to drop the key encoded as `key`.
+    pub fn send(&self, key: Vec<u8>) -> io::Result<()> {
+        let message = Invalidation {
+            sender: self.id,
+            key,
+        }
+        .to_bytes();
+        if message.len() > MAX_DATAGRAM {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "Key is too long"));
+        }
+        for target in &self.targets {
+            self.socket.send_to(&message, target)?;
+        }
+        Ok(())
+    }
+
+    /// Receive an encoded key invalidated by a peer, or `None` if none is pending.
+    /// Malformed datagrams and messages sent by this instance are skipped.
+    pub fn try_recv(&self) -> io::Result<Option<Vec<u8>>> {
+        let mut buffer = [0; MAX_DATAGRAM];
+        loop {
+            let len = match self.socket.recv(&mut buffer) {
+                Ok(len) => len,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(None),
+                Err(error) => return Err(error),
+            };
+            match Invalidation::from_bytes(&buffer[..len]) {
+                Some(invalidation) if invalidation.sender != self.id => {
+                    return Ok(Some(invalidation.key))
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+/// Id unlikely to be shared by two instances, even in one process or bound to one port.
+fn random_id() -> u64 {
+    static INSTANCES: AtomicU64 = AtomicU64::new(0);
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    // `RandomState` is seeded randomly, which makes this differ between processes as well.
+    RandomState::new().hash_one((
+        std::process::id(),
+        INSTANCES.fetch_add(1, Ordering::Relaxed),
+        now,
+    ))
+}
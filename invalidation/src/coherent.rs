@@ -0,0 +1,198 @@
+use crate::{Broadcaster, WireKey};
+use common::Cache;
+use std::{hash::Hash, marker::PhantomData};
+
+/// Counters of the invalidation traffic of a `CoherentCache`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CoherenceStats {
+    /// Invalidations sent to peers.
+    pub sent: u64,
+    /// Invalidations which could not be sent.
+    pub send_errors: u64,
+    /// Invalidations received from peers.
+    pub received: u64,
+    /// Received invalidations which removed an element.
+    pub applied: u64,
+}
+
+/// Cache telling its peers to drop keys written locally, and dropping keys written by them.
+pub struct CoherentCache<C, K, V> {
+    inner: C,
+    broadcaster: Broadcaster,
+    stats: CoherenceStats,
+    _marker: PhantomData<fn(K, V)>,
+}
+
+impl<C, K, V> CoherentCache<C, K, V>
+where
+    C: Cache<K, V>,
+    K: Hash + Eq + WireKey,
+    V: Clone,
+{
+    pub fn new(inner: C, broadcaster: Broadcaster) -> Self {
+        Self {
+            inner,
+            broadcaster,
+            stats: CoherenceStats::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn stats(&self) -> CoherenceStats {
+        self.stats
+    }
+
+    pub fn broadcaster(&self) -> &Broadcaster {
+        &self.broadcaster
+    }
+
+    pub fn broadcaster_mut(&mut self) -> &mut Broadcaster {
+        &mut self.broadcaster
+    }
+
+    /// Apply every invalidation received from peers so far and return how many there were.
+    /// Lookups do this first, so calling this is needed only to drop stale values eagerly.
+    pub fn apply_pending(&mut self) -> usize {
+        let mut received = 0;
+        // A socket error is treated as having nothing to receive; the next call retries.
+        while let Ok(Some(bytes)) = self.broadcaster.try_recv() {
+            received += 1;
+            if let Some(key) = K::decode(&bytes) {
+                if self.inner.remove(&key).is_some() {
+                    self.stats.applied += 1;
+                }
+            }
+        }
+        self.stats.received += received as u64;
+        received
+    }
+
+    fn invalidate_peers(&mut self, key: &K) {
+        match self.broadcaster.send(key.encode()) {
+            Ok(()) => self.stats.sent += 1,
+            Err(_) => self.stats.send_errors += 1,
+        }
+    }
+}
+
+impl<C, K, V> Cache<K, V> for CoherentCache<C, K, V>
+where
+    C: Cache<K, V>,
+    K: Hash + Eq + WireKey,
+    V: Clone,
+{
+    /// Insert a new key-value pair, telling peers to drop their value of `key`.
+    fn insert(&mut self, key: K, value: V) {
+        self.invalidate_peers(&key);
+        self.inner.insert(key, value);
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.apply_pending();
+        self.inner.get(key)
+    }
+
+    /// Look `key` up without applying pending invalidations, so that the value may be one a peer
+    /// has already replaced.
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        self.inner.get_quiet(key)
+    }
+
+    /// Remove an element, telling peers to drop theirs as well.
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.invalidate_peers(key);
+        self.inner.remove(key)
+    }
+
+    /// Remove all local elements. Peers are not told.
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        net::{Ipv4Addr, SocketAddr},
+        time::{Duration, Instant},
+    };
+    use sync_naive_lru::SyncNaiveLru;
+
+    type Lru = CoherentCache<SyncNaiveLru<u64, u64>, u64, u64>;
+
+    fn pair() -> (Lru, Lru) {
+        let localhost = SocketAddr::from((Ipv4Addr::LOCALHOST, 0));
+        let mut a = Broadcaster::bind(localhost).unwrap();
+        let mut b = Broadcaster::bind(localhost).unwrap();
+        a.add_peer(b.local_addr().unwrap());
+        b.add_peer(a.local_addr().unwrap());
+        (
+            CoherentCache::new(SyncNaiveLru::new(4), a),
+            CoherentCache::new(SyncNaiveLru::new(4), b),
+        )
+    }
+
+    /// Wait until `cache` has received `n` invalidations in total.
+    fn wait_for(cache: &mut Lru, n: u64) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.stats().received < n {
+            assert!(Instant::now() < deadline, "Invalidation was not delivered");
+            cache.apply_pending();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn insert_invalidates_peer() {
+        let (mut a, mut b) = pair();
+        b.insert(1, 10);
+        b.insert(2, 20);
+        wait_for(&mut a, 2);
+        a.insert(1, 11);
+        wait_for(&mut b, 1);
+        assert_eq!(b.get(&1), None);
+        assert_eq!(b.get(&2), Some(20));
+        assert_eq!(a.get(&1), Some(11));
+        assert_eq!(b.stats().applied, 1);
+        assert_eq!(a.stats().sent, 1);
+    }
+
+    #[test]
+    fn remove_invalidates_peer() {
+        let (mut a, mut b) = pair();
+        b.insert(1, 10);
+        wait_for(&mut a, 1);
+        a.remove(&1);
+        wait_for(&mut b, 1);
+        assert_eq!(b.get(&1), None);
+        assert_eq!(b.stats().applied, 1);
+    }
+
+    #[test]
+    fn multicast_on_one_port() {
+        let group = Ipv4Addr::new(239, 255, 42, 99);
+        let port = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let join = || {
+            let broadcaster = Broadcaster::join_multicast(group, port, Ipv4Addr::LOCALHOST);
+            CoherentCache::new(SyncNaiveLru::new(4), broadcaster.unwrap())
+        };
+        let (mut a, mut b): (Lru, Lru) = (join(), join());
+        a.insert(1, 10);
+        b.insert(1, 11);
+        wait_for(&mut a, 1);
+        wait_for(&mut b, 1);
+        assert_eq!(a.get(&1), None);
+        // Each instance skips its own message looped back by the group.
+        assert_eq!(a.stats().received, 1);
+        assert_eq!(b.stats().received, 1);
+    }
+}
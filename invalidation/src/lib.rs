@@ -0,0 +1,19 @@
+//! Experimental invalidation of keys across cache instances over UDP.
+//!
+//! Every instance owns a `Broadcaster`, which sends invalidations to either a multicast group or
+//! a fixed list of peers. `CoherentCache` wraps a local cache: writing a key tells every peer to
+//! drop its copy, and invalidations from peers are applied before each lookup. Values are never
+//! replicated, so a peer misses on the key afterwards and reloads it from the source of truth.
+//!
+//! Messages carry no ordering, so an invalidation arriving late also drops a value written on the
+//! receiving instance after it was sent, which costs a miss but never serves a stale value.
+//! Delivery is best effort, as UDP is; a lost datagram leaves a stale value on a peer. This crate
+//! exists to measure the overhead of keeping caches coherent this way, not to be relied on.
+
+mod broadcast;
+mod coherent;
+mod wire;
+
+pub use broadcast::Broadcaster;
+pub use coherent::{CoherenceStats, CoherentCache};
+pub use wire::WireKey;
@@ -0,0 +1,91 @@
+/// Key which can be sent in an invalidation message.
+pub trait WireKey: Sized {
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decode a key encoded by `encode`, or `None` if `bytes` is not one.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+impl WireKey for Vec<u8> {
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl WireKey for String {
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+impl WireKey for u64 {
+    fn encode(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(u64::from_le_bytes)
+    }
+}
+
+/// Message telling peers to drop `key`.
+/// On the wire this is the id of the sender followed by the encoded key.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Invalidation {
+    pub(crate) sender: u64,
+    pub(crate) key: Vec<u8>,
+}
+
+impl Invalidation {
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.sender.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&self.key);
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 8 {
+            return None;
+        }
+        let (sender, key) = bytes.split_at(8);
+        Some(Self {
+            sender: u64::from_le_bytes(sender.try_into().ok()?),
+            key: key.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_keys() {
+        assert_eq!(u64::decode(&42u64.encode()), Some(42));
+        assert_eq!(u64::decode(b"short"), None);
+        let key = String::from("users/1");
+        assert_eq!(String::decode(&key.encode()), Some(key));
+        assert_eq!(String::decode(&[0xff]), None);
+    }
+
+    #[test]
+    fn round_trip_invalidation() {
+        let invalidation = Invalidation {
+            sender: 7,
+            key: b"key".to_vec(),
+        };
+        assert_eq!(
+            Invalidation::from_bytes(&invalidation.to_bytes()),
+            Some(invalidation)
+        );
+        assert_eq!(Invalidation::from_bytes(&[1, 2, 3]), None);
+    }
+}
@@ -0,0 +1,251 @@
+use common::{
+    builder::{CacheConfig, FromConfig, TieBreak},
+    rng::Rng,
+    Cache,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+struct Entry<V> {
+    value: V,
+    frequency: u64,
+    /// Rank among elements of the same frequency given by the tie-break policy.
+    order: u64,
+    /// Number unique to the element, set when it is inserted.
+    id: u64,
+}
+
+/// LFU cache evicting the least frequently accessed element.
+/// Among elements accessed equally often, the victim is chosen by `TieBreak`, which measurably
+/// changes the hit ratio on skewed traces.
+///
+/// Elements are ordered for eviction in a `BTreeMap` keyed by frequency, tie-break rank and id, so
+/// every operation takes O(log n). Frequencies are never decayed.
+pub struct Lfu<K, V> {
+    map: HashMap<K, Entry<V>>,
+    queue: BTreeMap<(u64, u64, u64), K>,
+    capacity: usize,
+    tie_break: TieBreak,
+    rng: Rng,
+    /// Number of accesses so far, used as a clock.
+    tick: u64,
+}
+
+impl<K, V> Lfu<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Create a cache breaking ties by LRU.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_tie_break(capacity, TieBreak::Lru)
+    }
+
+    pub fn with_tie_break(capacity: usize, tie_break: TieBreak) -> Self {
+        let seed = match tie_break {
+            TieBreak::Random { seed } => seed,
+            _ => 0,
+        };
+        Self {
+            map: HashMap::new(),
+            queue: BTreeMap::new(),
+            capacity,
+            tie_break,
+            rng: Rng::new(seed),
+            tick: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn tie_break(&self) -> TieBreak {
+        self.tie_break
+    }
+
+    /// Number of accesses to `key` since it was inserted, including the insert.
+    pub fn frequency(&self, key: &K) -> Option<u64> {
+        self.map.get(key).map(|entry| entry.frequency)
+    }
+
+    fn order(&mut self, id: u64) -> u64 {
+        match self.tie_break {
+            TieBreak::Lru => self.tick,
+            TieBreak::Fifo => id,
+            TieBreak::Random { .. } => self.rng.next_u64(),
+        }
+    }
+
+    /// Count an access to the element of `key`, which must be present.
+    fn touch(&mut self, key: &K) {
+        self.tick += 1;
+        let (frequency, order, id) = {
+            let entry = &self.map[key];
+            (entry.frequency, entry.order, entry.id)
+        };
+        let queued = self
+            .queue
+            .remove(&(frequency, order, id))
+            .expect("Every element is queued");
+        let order = self.order(id);
+        let entry = self.map.get_mut(key).expect("Element is present");
+        entry.frequency += 1;
+        entry.order = order;
+        self.queue.insert((entry.frequency, order, id), queued);
+    }
+}
+
+impl<K, V> FromConfig for Lfu<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn from_config(config: CacheConfig) -> Self {
+        Self::with_tie_break(config.capacity, config.tie_break)
+    }
+}
+
+impl<K, V> Cache<K, V> for Lfu<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Insert a new key-value pair.
+    /// If the number of existing elements is `capacity`, remove least-frequently accessed one.
+    /// Inserting an existing key replaces its value and counts as an access.
+    fn insert(&mut self, key: K, value: V) {
+        if let Some(entry) = self.map.get_mut(&key) {
+            entry.value = value;
+            self.touch(&key);
+            return;
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+        if self.map.len() == self.capacity {
+            let (_, victim) = self.queue.pop_first().expect("Cache is full");
+            self.map.remove(&victim);
+        }
+        self.tick += 1;
+        let id = self.tick;
+        let order = self.order(id);
+        self.queue.insert((1, order, id), key.clone());
+        self.map.insert(
+            key,
+            Entry {
+                value,
+                frequency: 1,
+                order,
+                id,
+            },
+        );
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        if !self.map.contains_key(key) {
+            return None;
+        }
+        self.touch(key);
+        Some(self.map[key].value.clone())
+    }
+
+    /// Get the value of `key` without incrementing its frequency.
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        self.map.get(key).map(|entry| entry.value.clone())
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.map.remove(key)?;
+        self.queue.remove(&(entry.frequency, entry.order, entry.id));
+        Some(entry.value)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.queue.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::CacheBuilder;
+    use std::collections::HashSet;
+
+    common::cache_conformance_tests!(Lfu::new);
+
+    /// Cache of 2 where both elements have been accessed twice, 2 more recently than 1 but
+    /// inserted after it, and then 3 is inserted.
+    fn evict_on_tie(tie_break: TieBreak) -> Lfu<i32, i32> {
+        let mut lfu = Lfu::with_tie_break(2, tie_break);
+        lfu.insert(1, 10);
+        lfu.insert(2, 20);
+        lfu.get(&2);
+        lfu.get(&1);
+        lfu.insert(3, 30);
+        lfu
+    }
+
+    #[test]
+    fn evict_least_frequent() {
+        let mut lfu = Lfu::new(2);
+        lfu.insert(1, 10);
+        lfu.get(&1);
+        lfu.insert(2, 20);
+        lfu.insert(3, 30);
+        assert_eq!(lfu.get(&2), None);
+        assert_eq!(lfu.get(&1), Some(10));
+        assert_eq!(lfu.frequency(&1), Some(3));
+        assert_eq!(lfu.frequency(&3), Some(1));
+    }
+
+    #[test]
+    fn tie_break_by_lru() {
+        let mut lfu = evict_on_tie(TieBreak::Lru);
+        assert_eq!(lfu.get(&2), None);
+        assert_eq!(lfu.get(&1), Some(10));
+    }
+
+    #[test]
+    fn tie_break_by_fifo() {
+        let mut lfu = evict_on_tie(TieBreak::Fifo);
+        assert_eq!(lfu.get(&1), None);
+        assert_eq!(lfu.get(&2), Some(20));
+    }
+
+    #[test]
+    fn tie_break_at_random() {
+        // Every element is accessed once, so the victim of inserting 8 is drawn among all of them.
+        let victims = |seed| {
+            let mut lfu = Lfu::with_tie_break(8, TieBreak::Random { seed });
+            (0..8).for_each(|key| lfu.insert(key, key));
+            lfu.insert(8, 8);
+            (0..8)
+                .filter(|key| lfu.get(key).is_none())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(victims(1).len(), 1);
+        assert_eq!(victims(1), victims(1));
+        let distinct = (0..10).map(|seed| victims(seed)[0]).collect::<HashSet<_>>();
+        assert!(distinct.len() > 1, "Every seed evicted {:?}", distinct);
+    }
+
+    #[test]
+    fn build_with_tie_break() {
+        let lfu = CacheBuilder::new()
+            .capacity(4)
+            .tie_break(TieBreak::Fifo)
+            .build::<Lfu<i32, i32>>()
+            .unwrap();
+        assert_eq!(lfu.capacity(), 4);
+        assert_eq!(lfu.tie_break(), TieBreak::Fifo);
+    }
+}
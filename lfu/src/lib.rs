@@ -0,0 +1,4 @@
+pub mod lfu;
+
+pub use common::builder::TieBreak;
+pub use lfu::Lfu;
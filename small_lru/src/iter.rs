@@ -0,0 +1,26 @@
+use std::vec;
+
+use crate::lru::SmallLru;
+
+pub struct IntoIter<K, V> {
+    entries: vec::IntoIter<(K, V)>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next()
+    }
+}
+
+impl<K, V> IntoIterator for SmallLru<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter {
+            entries: self.entries.into_iter(),
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod iter;
+pub mod lru;
+
+pub use lru::SmallLru;
@@ -0,0 +1,311 @@
+use common::{
+    builder::{CacheConfig, FromConfig},
+    Cache,
+};
+use std::{collections::HashMap, hash::Hash, rc::Rc};
+
+/// Index standing for "no node", used in place of `None` for links.
+const NIL: usize = usize::MAX;
+
+struct Node<K, V> {
+    next: usize,
+    prev: usize,
+    key: Rc<K>,
+    value: V,
+}
+
+/// LRU cache whose nodes live in one arena allocated up front.
+/// The list has the same layout as `SyncNaiveLru`, but links are indices into the arena instead of
+/// `Rc`/`Weak` pointers, so every node of the cache is stored contiguously.
+///
+/// When the cache is full, the slot of the evicted element is reused for the inserted one, so no
+/// allocation for nodes happens after the arena is filled.
+pub struct ArenaLru<K, V> {
+    map: HashMap<Rc<K>, usize>,
+    nodes: Vec<Node<K, V>>,
+    head: usize,
+    tail: usize,
+    capacity: usize,
+}
+
+impl<K, V> ArenaLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            map: HashMap::with_capacity(capacity),
+            nodes: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            capacity,
+        }
+    }
+
+    /// Attach node at `index` to the head of linked list.
+    fn attach(&mut self, index: usize) {
+        self.nodes[index].prev = self.head;
+        self.nodes[index].next = NIL;
+        if self.head != NIL {
+            self.nodes[self.head].next = index;
+        } else {
+            self.tail = index;
+        }
+        self.head = index;
+    }
+
+    /// Point links and the map entry of the node moved to `to` at its new slot.
+    fn relink(&mut self, to: usize) {
+        let Node { prev, next, .. } = self.nodes[to];
+        if prev != NIL {
+            self.nodes[prev].next = to;
+        } else {
+            self.tail = to;
+        }
+        if next != NIL {
+            self.nodes[next].prev = to;
+        } else {
+            self.head = to;
+        }
+        *self
+            .map
+            .get_mut(&self.nodes[to].key)
+            .expect("Every node has its map entry") = to;
+    }
+
+    fn detach(&mut self, index: usize) {
+        let Node { prev, next, .. } = self.nodes[index];
+        if prev != NIL {
+            self.nodes[prev].next = next;
+        } else {
+            // The node is tail element.
+            self.tail = next;
+        }
+        if next != NIL {
+            self.nodes[next].prev = prev;
+        } else {
+            // The node is head element.
+            self.head = prev;
+        }
+    }
+}
+
+impl<K, V> FromConfig for ArenaLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    fn from_config(config: CacheConfig) -> Self {
+        Self::new(config.capacity)
+    }
+}
+
+impl<K, V> Cache<K, V> for ArenaLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    /// Insert a new key-value pair.
+    /// If the number of existing elements is `capacity`, the slot of least-recently accessed one
+    /// is reused.
+    fn insert(&mut self, key: K, value: V) {
+        if let Some(&index) = self.map.get(&key) {
+            self.nodes[index].value = value;
+            self.detach(index);
+            self.attach(index);
+            return;
+        }
+
+        let key = Rc::new(key);
+        let index = if self.nodes.len() < self.capacity {
+            self.nodes.push(Node {
+                next: NIL,
+                prev: NIL,
+                key: Rc::clone(&key),
+                value,
+            });
+            self.nodes.len() - 1
+        } else if self.capacity == 0 {
+            return;
+        } else {
+            let index = self.tail;
+            self.detach(index);
+            self.map.remove(&self.nodes[index].key);
+            self.nodes[index].key = Rc::clone(&key);
+            self.nodes[index].value = value;
+            index
+        };
+        self.map.insert(key, index);
+        self.attach(index);
+    }
+
+    /// Get clone of a value corresponding to `key`.
+    /// This moves accessed element to head of the list.
+    fn get(&mut self, key: &K) -> Option<V> {
+        let index = *self.map.get(key)?;
+        self.detach(index);
+        self.attach(index);
+        Some(self.nodes[index].value.clone())
+    }
+
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        let index = *self.map.get(key)?;
+        Some(self.nodes[index].value.clone())
+    }
+
+    /// Remove an element corresponding to `key` and return its value.
+    /// The last node of the arena is moved into the freed slot to keep the arena dense.
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.map.remove(key)?;
+        self.detach(index);
+        let removed = self.nodes.swap_remove(index);
+        if index < self.nodes.len() {
+            self.relink(index);
+        }
+        Some(removed.value)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.nodes.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+pub struct IntoIter<K, V> {
+    nodes: Vec<Option<Node<K, V>>>,
+    current: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current == NIL {
+            return None;
+        }
+        let node = self.nodes[self.current]
+            .take()
+            .expect("Each node is visited once");
+        self.current = node.next;
+        let key = Rc::try_unwrap(node.key)
+            .ok()
+            .expect("Keys are not shared outside of the cache");
+        Some((key, node.value))
+    }
+}
+
+impl<K, V> IntoIterator for ArenaLru<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        // Release keys held by the map so that they can be moved out of the nodes.
+        drop(self.map);
+        IntoIter {
+            nodes: self.nodes.into_iter().map(Some).collect(),
+            current: self.tail,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SyncNaiveLru;
+    use common::CacheBuilder;
+
+    common::cache_conformance_tests!(ArenaLru::new);
+
+    proptest::proptest! {
+        #[test]
+        fn matches_reference_model(capacity in 0usize..8, ops in common::model::operations(16)) {
+            common::model::check_against_reference(ArenaLru::new(capacity), capacity, &ops);
+        }
+
+        #[test]
+        fn matches_sync_naive_lru(capacity in 0usize..8, ops in common::model::operations(16)) {
+            common::model::check_differential(
+                SyncNaiveLru::new(capacity),
+                ArenaLru::new(capacity),
+                &ops,
+            );
+        }
+    }
+
+    fn setup_lru_with_capacity_3() -> ArenaLru<i32, i32> {
+        let mut lru = ArenaLru::new(3);
+        [(1, 2), (3, 4), (5, 6)]
+            .iter()
+            .for_each(|kv| lru.insert(kv.0, kv.1));
+        lru
+    }
+
+    #[test]
+    fn just_insert() {
+        let lru = setup_lru_with_capacity_3();
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(1, 2), (3, 4), (5, 6)]
+        );
+    }
+
+    #[test]
+    fn exceeding_insert_reuses_slot() {
+        let mut lru = ArenaLru::new(3);
+        let expected = [(1, 2), (3, 4), (5, 6), (7, 8)];
+        expected.iter().for_each(|kv| lru.insert(kv.0, kv.1));
+
+        assert_eq!(lru.nodes.len(), 3);
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(3, 4), (5, 6), (7, 8)]
+        );
+    }
+
+    #[test]
+    fn get_reorders_entry() {
+        let mut lru = setup_lru_with_capacity_3();
+        assert_eq!(lru.get(&3), Some(4));
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(1, 2), (5, 6), (3, 4)]
+        );
+    }
+
+    #[test]
+    fn insert_existing_key_updates_value() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.insert(1, 10);
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(3, 4), (5, 6), (1, 10)]
+        );
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut lru = ArenaLru::new(0);
+        lru.insert(1, 2);
+        assert_eq!(lru.get(&1), None);
+    }
+
+    #[test]
+    fn build_from_builder() {
+        let mut lru = CacheBuilder::new()
+            .capacity(1)
+            .build::<ArenaLru<i32, i32>>()
+            .unwrap();
+        lru.insert(1, 2);
+        lru.insert(3, 4);
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(lru.get(&3), Some(4));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::SyncNaiveLru;
+use bytes::Bytes;
+use common::{Cache, InsertError};
+use std::hash::Hash;
+
+/// LRU cache of byte buffers, e.g. HTTP bodies or blobs, whose capacity is the total number of
+/// bytes of values.
+/// Values are stored as `Bytes`, so a hit only bumps a reference count instead of copying the
+/// buffer, and a `Vec<u8>` is converted into one without copying.
+pub struct BytesCache<K> {
+    lru: SyncNaiveLru<K, Bytes>,
+}
+
+impl<K: Hash + Eq> BytesCache<K> {
+    /// Create a cache holding values of at most `capacity` bytes in total.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lru: SyncNaiveLru::with_weigher(capacity, |_, value: &Bytes| value.len()),
+        }
+    }
+
+    /// Maximum total number of bytes of values.
+    pub fn capacity(&self) -> usize {
+        self.lru.capacity()
+    }
+
+    /// Total number of bytes of values.
+    pub fn bytes(&self) -> usize {
+        self.lru.weight()
+    }
+
+    /// Same as `insert`, taking anything convertible into `Bytes` such as `Vec<u8>`.
+    pub fn put(&mut self, key: K, value: impl Into<Bytes>) {
+        self.lru.insert(key, value.into());
+    }
+}
+
+impl<K: Hash + Eq> Cache<K, Bytes> for BytesCache<K> {
+    /// Insert a new key-value pair, evicting least-recently accessed elements until the bytes fit.
+    /// A value larger than the whole capacity is not cached.
+    fn insert(&mut self, key: K, value: Bytes) {
+        self.lru.insert(key, value);
+    }
+
+    fn try_insert(&mut self, key: K, value: Bytes) -> Result<(), InsertError> {
+        self.lru.try_insert(key, value)
+    }
+
+    fn get(&mut self, key: &K) -> Option<Bytes> {
+        self.lru.get(key)
+    }
+
+    fn get_quiet(&self, key: &K) -> Option<Bytes> {
+        self.lru.get_quiet(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<Bytes> {
+        self.lru.remove(key)
+    }
+
+    fn clear(&mut self) {
+        self.lru.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.lru.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capacity_in_bytes() {
+        let mut cache = BytesCache::new(10);
+        cache.put("a", vec![0; 4]);
+        cache.put("b", vec![1; 4]);
+        assert_eq!(cache.bytes(), 8);
+        cache.put("c", vec![2; 4]);
+        assert_eq!(cache.get(&"a"), None);
+        assert_eq!(cache.bytes(), 8);
+        assert_eq!(
+            cache.try_insert("d", Bytes::from(vec![3; 11])),
+            Err(InsertError::TooHeavy {
+                weight: 11,
+                capacity: 10
+            })
+        );
+    }
+
+    #[test]
+    fn hits_share_the_buffer() {
+        let mut cache = BytesCache::new(16);
+        cache.put(1, Bytes::from_static(b"body"));
+        let first = cache.get(&1).unwrap();
+        let second = cache.get(&1).unwrap();
+        assert_eq!(first.as_ptr(), second.as_ptr());
+        assert_eq!(&first[..], b"body");
+    }
+}
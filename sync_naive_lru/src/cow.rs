@@ -0,0 +1,43 @@
+use crate::SyncNaiveLru;
+use common::Cache;
+use std::{borrow::Cow, hash::Hash};
+
+/// LRU cache of values which are mostly `'static` data, e.g. entries of constant lookup tables.
+/// A borrowed value is cloned by copying a reference, so hits on it never allocate; owned values
+/// can still be stored for the rest.
+pub type CowLru<K, T> = SyncNaiveLru<K, Cow<'static, T>>;
+
+impl<K, T> SyncNaiveLru<K, Cow<'static, T>>
+where
+    K: Hash + Eq,
+    T: ToOwned + ?Sized + 'static,
+{
+    /// Insert a reference to `'static` data, which is never cloned by the cache.
+    pub fn insert_static(&mut self, key: K, value: &'static T) {
+        self.insert(key, Cow::Borrowed(value));
+    }
+
+    /// Same as `get`, but converted into an owned value for callers which need ownership.
+    pub fn get_owned(&mut self, key: &K) -> Option<T::Owned> {
+        self.get(key).map(Cow::into_owned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrowed_and_owned_values() {
+        static GREETING: &str = "hello";
+        let mut lru: CowLru<u32, str> = SyncNaiveLru::new(2);
+        lru.insert_static(1, GREETING);
+        lru.insert(2, Cow::Owned(String::from("world")));
+        match lru.get(&1) {
+            Some(Cow::Borrowed(value)) => assert!(std::ptr::eq(value, GREETING)),
+            other => panic!("Expected a borrowed value, got {:?}", other),
+        }
+        assert_eq!(lru.get_owned(&2), Some(String::from("world")));
+        assert_eq!(lru.get_owned(&3), None);
+    }
+}
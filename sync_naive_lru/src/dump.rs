@@ -0,0 +1,83 @@
+//! Human-readable dump of a `SyncNaiveLru` for support tooling and bug reports.
+//!
+//! The format is meant to be read, not parsed back: it may change between versions and is not
+//! a way to persist a cache.
+
+use crate::SyncNaiveLru;
+use common::Cache;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::hash::Hash;
+
+impl<K, V> SyncNaiveLru<K, V>
+where
+    K: Hash + Eq + Serialize,
+    V: Clone + Serialize,
+{
+    /// Pretty-printed JSON of the capacity and elements from least to most recently accessed,
+    /// with for each element:
+    /// - `age`: number of inserts into the cache since its value was inserted, 0 for the last one
+    /// - `accesses`: number of hits since then
+    pub fn dump_json(&self) -> serde_json::Result<String> {
+        let mut entries = Vec::with_capacity(self.len());
+        let mut current = self.tail.clone();
+        while let Some(node) = current {
+            let node_ref = node.as_ref().borrow();
+            entries.push(json!({
+                "key": serde_json::to_value(&*node_ref.key)?,
+                "value": serde_json::to_value(&node_ref.value)?,
+                "age": self.version - node_ref.version,
+                "accesses": node_ref.accesses,
+            }));
+            current = node_ref.next.clone();
+        }
+        serde_json::to_string_pretty(&json!({
+            "capacity": self.capacity(),
+            "len": self.len(),
+            "weight": self.weight(),
+            "entries": Value::Array(entries),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump_in_recency_order() {
+        let mut lru = SyncNaiveLru::new(3);
+        lru.insert("a", 1);
+        lru.insert("b", 2);
+        lru.insert("c", 3);
+        lru.get(&"a");
+        lru.get(&"a");
+        lru.insert("c", 30);
+        let dump: Value = serde_json::from_str(&lru.dump_json().unwrap()).unwrap();
+        assert_eq!(
+            dump,
+            json!({
+                "capacity": 3,
+                "len": 3,
+                "weight": 3,
+                "entries": [
+                    { "key": "b", "value": 2, "age": 2, "accesses": 0 },
+                    { "key": "a", "value": 1, "age": 3, "accesses": 2 },
+                    { "key": "c", "value": 30, "age": 0, "accesses": 0 },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn dump_split_off_cache() {
+        let mut lru = SyncNaiveLru::new(3);
+        lru.insert("a", 1);
+        lru.insert("b", 2);
+        let mut cold = lru.split_off_cold(1);
+        cold.insert("c", 3);
+        let dump: Value = serde_json::from_str(&cold.dump_json().unwrap()).unwrap();
+        assert_eq!(dump["entries"][0]["age"], 2);
+        assert_eq!(dump["entries"][1]["age"], 0);
+    }
+}
@@ -1,41 +1,44 @@
-use std::{hash::Hash, rc::Rc};
+use std::rc::Rc;
 
 use crate::lru::{NodeRef, SyncNaiveLru};
 
+/// Owning iterator over the elements of a `SyncNaiveLru`, from least to most recently accessed.
+/// The map and the other links are gone once the cache is consumed, so every node and key is
+/// only referenced from here and is moved out instead of cloned.
 pub struct IntoIter<K, V> {
     current: Option<NodeRef<K, V>>,
 }
 
-impl<K, V> Iterator for IntoIter<K, V>
-where
-    K: Hash + Eq + Clone,
-    V: Clone,
-{
+impl<K, V> Iterator for IntoIter<K, V> {
     type Item = (K, V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.current.take() {
-            None => None,
-            Some(current) => {
-                let key = current.as_ref().borrow().key.clone();
-                let value = current.as_ref().borrow().value.clone();
-                let item = ((*key).clone(), value);
-                self.current = current.borrow().next.as_ref().map(Rc::clone);
-                Some(item)
-            }
+        let current = self.current.take()?;
+        self.current = current.borrow_mut().next.take();
+        let node = Rc::into_inner(current)
+            .expect("Consumed cache must not share nodes")
+            .into_inner();
+        let key = Rc::into_inner(node.key).expect("Consumed cache must not share keys");
+        Some((key, node.value))
+    }
+}
+
+impl<K, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        let mut current = self.current.take();
+        while let Some(node) = current {
+            current = node.borrow_mut().next.take();
         }
     }
 }
 
-impl<K, V> IntoIterator for SyncNaiveLru<K, V>
-where
-    K: Hash + Eq + Clone,
-    V: Clone,
-{
+impl<K, V> IntoIterator for SyncNaiveLru<K, V> {
     type Item = (K, V);
     type IntoIter = IntoIter<K, V>;
 
-    fn into_iter(self) -> IntoIter<K, V> {
-        IntoIter { current: self.tail }
+    fn into_iter(mut self) -> IntoIter<K, V> {
+        IntoIter {
+            current: self.tail.take(),
+        }
     }
 }
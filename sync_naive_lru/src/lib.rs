@@ -1,4 +1,22 @@
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "bytes")]
+pub mod bytes_cache;
+pub mod cow;
+#[cfg(feature = "json")]
+mod dump;
 pub mod iter;
 pub mod lru;
+pub mod partition;
+pub mod pressure;
+pub mod weak;
 
+#[cfg(feature = "arena")]
+pub use arena::ArenaLru;
+#[cfg(feature = "bytes")]
+pub use bytes_cache::BytesCache;
+pub use cow::CowLru;
 pub use lru::SyncNaiveLru;
+pub use partition::PartitionedCache;
+pub use pressure::PressureHandle;
+pub use weak::WeakValueLru;
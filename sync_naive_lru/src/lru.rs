@@ -1,10 +1,17 @@
-use common::Cache;
+use crate::pressure::PressureHandle;
+use common::{
+    adaptive::Resize,
+    builder::{CacheConfig, FromConfig, Weighted},
+    heap::{heap_weight, HeapSize},
+    Cache, CacheEvent, FrozenCache, GhostHistory, InsertError, DEFAULT_CAPACITY,
+};
 use std::{
-    borrow::Borrow,
     cell::RefCell,
-    collections::HashMap,
+    collections::{hash_map::RandomState, HashMap},
     hash::Hash,
+    mem,
     rc::{Rc, Weak},
+    sync::mpsc::{self, Receiver},
 };
 
 pub(crate) type NodeRef<K, V> = Rc<RefCell<Node<K, V>>>;
@@ -15,6 +22,11 @@ pub(crate) struct Node<K, V> {
     pub(crate) prev: Option<NodeWeakRef<K, V>>,
     pub(crate) key: Rc<K>,
     pub(crate) value: V,
+    /// Whether the node lies in the probationary segment of midpoint insertion.
+    old: bool,
+    pub(crate) version: u64,
+    /// Number of hits since the value was last inserted.
+    pub(crate) accesses: u64,
 }
 
 impl<K, V> Node<K, V> {
@@ -24,20 +36,52 @@ impl<K, V> Node<K, V> {
             prev: None,
             key,
             value,
+            old: false,
+            version: 0,
+            accesses: 0,
         }
     }
 }
 
+/// State of midpoint insertion.
+/// The list is split into a young segment on the head side and an old, probationary segment on
+/// the tail side. New elements enter at the head of the old segment, and move to the head of the
+/// whole list only when accessed again.
+struct Midpoint<K, V> {
+    /// Share of elements kept in the old segment.
+    old_ratio: f64,
+    /// Most recently accessed element of the old segment.
+    old_head: Option<NodeRef<K, V>>,
+    old_len: usize,
+}
+
+/// Function giving the weight of an element from its key and value.
+pub type Weigher<K, V> = Rc<dyn Fn(&K, &V) -> usize>;
+
+/// Subscriber of events, returning `false` once it is gone.
+type Listener<K> = Box<dyn FnMut(CacheEvent<&K>) -> bool>;
+
 /// LRU cache implemented by hash map and doubly-linked list.
 /// more recently accessed element lies head of the list and least recently accessed one lies the
 /// opposite.
 ///
+/// With a weigher, `capacity` bounds the total weight of elements instead of their number; every
+/// element weighs 1 otherwise.
+///
 /// This can be used only in synchronous context; not for multi-threaded or async runtime.
 pub struct SyncNaiveLru<K, V> {
     map: HashMap<Rc<K>, NodeRef<K, V>>,
     head: Option<NodeRef<K, V>>,
     pub(crate) tail: Option<NodeRef<K, V>>,
     capacity: usize,
+    weigher: Option<Weigher<K, V>>,
+    weight: usize,
+    midpoint: Option<Midpoint<K, V>>,
+    /// Version given to the last inserted element.
+    pub(crate) version: u64,
+    pressure: Option<PressureHandle>,
+    listeners: Vec<Listener<K>>,
+    ghosts: Option<GhostHistory>,
 }
 
 impl<K, V> SyncNaiveLru<K, V>
@@ -51,22 +95,550 @@ where
             head: None,
             tail: None,
             capacity,
+            weigher: None,
+            weight: 0,
+            midpoint: None,
+            version: 0,
+            pressure: None,
+            listeners: Vec::new(),
+            ghosts: None,
+        }
+    }
+
+    /// Create a cache whose elements weigh `weigher(key, value)` in total at most `capacity`.
+    /// `weigher` must return the same weight for an element every time it is called.
+    pub fn with_weigher(capacity: usize, weigher: impl Fn(&K, &V) -> usize + 'static) -> Self {
+        let mut lru = Self::new(capacity);
+        lru.weigher = Some(Rc::new(weigher));
+        lru
+    }
+
+    /// Create a cache holding at most `capacity` bytes, estimated by `heap_weight` for keys and
+    /// values alike.
+    pub fn with_heap_weigher(capacity: usize) -> Self
+    where
+        K: HeapSize + 'static,
+        V: HeapSize + 'static,
+    {
+        Self::with_weigher(capacity, heap_weight)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Insert new elements at `insertion_point` of the list instead of its head, where 0 is the
+    /// head and 1 is the tail, or at the head again with `None`.
+    /// An element inserted this way moves to the head once it is accessed again, so a scan of
+    /// keys accessed only once evicts just the elements behind the insertion point. Elements
+    /// already in the cache are treated as accessed twice.
+    pub fn set_midpoint_insertion(&mut self, insertion_point: Option<f64>) {
+        if let Some(midpoint) = self.midpoint.take() {
+            let mut current = midpoint.old_head;
+            while let Some(node) = current {
+                node.borrow_mut().old = false;
+                current = node.as_ref().borrow().prev.as_ref().and_then(Weak::upgrade);
+            }
+        }
+        if let Some(insertion_point) = insertion_point {
+            assert!(
+                (0.0..=1.0).contains(&insertion_point),
+                "Insertion point must be in [0, 1]"
+            );
+            self.midpoint = Some(Midpoint {
+                old_ratio: 1.0 - insertion_point,
+                old_head: None,
+                old_len: 0,
+            });
+            self.rebalance_midpoint();
+        }
+    }
+
+    /// Remember hashes of the last `capacity` evicted keys, or stop remembering them with `None`.
+    pub fn set_ghost_history(&mut self, capacity: Option<usize>) {
+        self.ghosts = capacity.map(GhostHistory::new);
+    }
+
+    /// Whether `key` is among the keys last evicted, as far as the ghost history set by
+    /// `set_ghost_history` remembers, and has not been inserted again since.
+    /// This can report a key whose hash collides with an evicted one.
+    pub fn was_recently_evicted(&self, key: &K) -> bool {
+        self.ghosts
+            .as_ref()
+            .is_some_and(|ghosts| ghosts.contains(key))
+    }
+
+    /// Total weight of elements, which is the number of them without a weigher.
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Change the maximum number (or total weight) of elements.
+    /// If the cache holds more than new `capacity`, least-recently accessed elements are evicted
+    /// at once.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict_overweight();
+        self.rebalance_midpoint();
+    }
+
+    /// Evict least-recently accessed elements until at most `fraction` of the current weight is
+    /// left, and return how many were evicted.
+    pub fn evict_to(&mut self, fraction: f64) -> usize {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "Fraction must be in [0, 1]"
+        );
+        let evicted = self.shed_to((self.weight as f64 * fraction) as usize);
+        self.rebalance_midpoint();
+        evicted
+    }
+
+    /// Evict least-recently accessed elements until their weight adds up to at least `bytes`
+    /// (or the cache is empty), and return how many were evicted.
+    /// The name assumes a weigher returning the size of values in bytes.
+    pub fn evict_bytes(&mut self, bytes: usize) -> usize {
+        let evicted = self.shed_to(self.weight.saturating_sub(bytes));
+        self.rebalance_midpoint();
+        evicted
+    }
+
+    /// Handle to ask this cache to shed elements from elsewhere, e.g. another thread.
+    /// Every call returns a handle to the same request.
+    pub fn pressure_handle(&mut self) -> PressureHandle {
+        self.pressure
+            .get_or_insert_with(PressureHandle::new)
+            .clone()
+    }
+
+    /// Apply a request made through `PressureHandle`, if any, and return how many elements were
+    /// evicted. Lookups and insertions call this, so only an idle cache needs it called.
+    pub fn relieve_pressure(&mut self) -> usize {
+        match self.pressure.as_ref().and_then(PressureHandle::take) {
+            Some(keep) => self.evict_to(keep),
+            None => 0,
+        }
+    }
+
+    /// Move `n` least-recently accessed elements (or all of them if there are fewer) into a new
+    /// cache of the same capacity, keeping their order.
+    /// The cold segment is cut off the tail of the list in one pass, so no node is reallocated.
+    /// Moved elements are still cached, so subscribers are not told about them.
+    pub fn split_off_cold(&mut self, n: usize) -> Self {
+        let mut cold = Self::new(self.capacity);
+        cold.weigher = self.weigher.clone();
+        // Moved elements keep their versions, so new ones must not reuse them.
+        cold.version = self.version;
+        cold.midpoint = self.midpoint.as_ref().map(|midpoint| Midpoint {
+            old_ratio: midpoint.old_ratio,
+            old_head: None,
+            old_len: 0,
+        });
+        let n = n.min(self.map.len());
+        if n == 0 {
+            return cold;
+        }
+
+        cold.tail = self.tail.take();
+        let mut current = cold.tail.clone();
+        for _ in 0..n {
+            let node = current.expect("There must be at least `n` elements");
+            let (key, entry) = self
+                .map
+                .remove_entry(&node.as_ref().borrow().key)
+                .expect("Every node has its map entry");
+            let weight = self.weigh(&key, &entry.as_ref().borrow().value);
+            self.weight -= weight;
+            cold.weight += weight;
+            cold.map.insert(key, entry);
+            self.forget_old(&node);
+            current = node.as_ref().borrow().next.clone();
+            cold.head = Some(node);
+        }
+
+        let hot_tail = cold
+            .head
+            .as_ref()
+            .and_then(|head| head.borrow_mut().next.take());
+        match hot_tail.as_ref() {
+            Some(tail) => tail.borrow_mut().prev = None,
+            None => self.head = None,
+        }
+        self.tail = hot_tail;
+        self.rebalance_midpoint();
+        cold.rebalance_midpoint();
+        cold
+    }
+
+    /// Move every element of `other` into this cache.
+    /// Elements of `other` become more recent than those of this cache, keeping their order, so
+    /// for a key in both caches the value of `other` wins. Least-recently accessed elements are
+    /// evicted as needed.
+    pub fn merge(&mut self, other: Self) {
+        for (key, value) in other {
+            self.insert(key, value);
+        }
+    }
+
+    /// Turn into a read-only cache which can be shared between threads.
+    pub fn freeze(self) -> FrozenCache<K, V> {
+        self.into_iter().collect()
+    }
+
+    /// Number of elements accessed more recently than the element of `key`, so 0 for the most
+    /// recently accessed one, without promoting it.
+    /// This walks the list from its head, taking time linear in the rank.
+    pub fn rank_of(&self, key: &K) -> Option<usize> {
+        let target = self.map.get(key)?;
+        let mut rank = 0;
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            if Rc::ptr_eq(&node, target) {
+                return Some(rank);
+            }
+            rank += 1;
+            current = node.as_ref().borrow().prev.as_ref().and_then(Weak::upgrade);
+        }
+        unreachable!("Element in the map is not in the list")
+    }
+
+    /// Same as `get`, also returning the version of the element.
+    /// Every insert gives an element a new version, unique within the cache.
+    pub fn get_versioned(&mut self, key: &K) -> Option<(V, u64)> {
+        let value = self.get(key)?;
+        let version = self.map[key].as_ref().borrow().version;
+        Some((value, version))
+    }
+
+    /// Insert `value` only if the element of `key` still has `expected_version`, returning the
+    /// new version.
+    /// This lets a read-modify-write through the cache detect that the element was changed or
+    /// removed in between, by `InsertError::VersionMismatch`, instead of overwriting it.
+    pub fn insert_if_version(
+        &mut self,
+        key: K,
+        value: V,
+        expected_version: u64,
+    ) -> Result<u64, InsertError> {
+        let actual = self
+            .map
+            .get(&key)
+            .map(|node| node.as_ref().borrow().version);
+        if actual != Some(expected_version) {
+            return Err(InsertError::VersionMismatch {
+                expected: expected_version,
+                actual,
+            });
+        }
+        self.try_insert(key, value)?;
+        Ok(self.version)
+    }
+
+    /// Keep only elements for which `f` returns `true`, without changing their order.
+    /// This visits every element, so it takes time proportional to `len`.
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &V) -> bool) {
+        let rejected = self
+            .map
+            .iter()
+            .filter(|(key, node)| !f(key, &node.as_ref().borrow().value))
+            .map(|(key, _)| Rc::clone(key))
+            .collect::<Vec<_>>();
+        for key in rejected {
+            self.remove(&key);
         }
     }
 
+    /// Remove every element whose key satisfies `f` and return how many were removed.
+    /// Unlike `retain`, this walks the list once and unlinks matching nodes as it goes, without
+    /// collecting their keys first.
+    pub fn invalidate_all_matching(&mut self, mut f: impl FnMut(&K) -> bool) -> usize {
+        let mut removed = 0;
+        let mut current = self.tail.clone();
+        while let Some(node) = current {
+            current = node.as_ref().borrow().next.clone();
+            let key = Rc::clone(&node.as_ref().borrow().key);
+            if !f(&key) {
+                continue;
+            }
+            self.detach(Rc::clone(&node));
+            self.map.remove(&key);
+            self.weight -= self.weigh(&key, &node.as_ref().borrow().value);
+            self.emit(CacheEvent::Removed(&key));
+            removed += 1;
+        }
+        self.rebalance_midpoint();
+        removed
+    }
+
+    fn weigh(&self, key: &K, value: &V) -> usize {
+        self.weigher
+            .as_ref()
+            .map_or(1, |weigher| weigher(key, value))
+    }
+
+    fn weigh_node(&self, node: &NodeRef<K, V>) -> usize {
+        let node = node.as_ref().borrow();
+        self.weigh(&node.key, &node.value)
+    }
+
+    /// Check invariants of the inner structure, panicking if any of them is broken.
+    /// This walks the whole list, so it is meant for tests and fuzzing.
+    pub fn validate(&self) {
+        assert!(self.weight <= self.capacity, "Weight exceeds capacity");
+
+        let mut count = 0;
+        let mut weight = 0;
+        let mut prev: Option<NodeRef<K, V>> = None;
+        let mut current = self.tail.clone();
+        while let Some(node) = current {
+            let node_ref = node.as_ref().borrow();
+            let expected_prev = node_ref.prev.as_ref().and_then(Weak::upgrade);
+            assert!(
+                match (&prev, &expected_prev) {
+                    (Some(prev), Some(expected)) => Rc::ptr_eq(prev, expected),
+                    (None, None) => true,
+                    _ => false,
+                },
+                "`prev` does not point to the previous node"
+            );
+            let entry = self.map.get(&node_ref.key).expect("Node is not in the map");
+            assert!(Rc::ptr_eq(entry, &node), "Map points to another node");
+            weight += self.weigh(&node_ref.key, &node_ref.value);
+
+            count += 1;
+            assert!(count <= self.map.len(), "List is longer than the map");
+            current = node_ref.next.clone();
+            drop(node_ref);
+            prev = Some(node);
+        }
+        assert_eq!(count, self.map.len(), "List is shorter than the map");
+        self.validate_midpoint();
+        assert_eq!(weight, self.weight, "Weight is not the sum of elements");
+        assert!(
+            match (&prev, &self.head) {
+                (Some(last), Some(head)) => Rc::ptr_eq(last, head),
+                (None, None) => true,
+                _ => false,
+            },
+            "`head` is not the last node of the list"
+        );
+    }
+
+    /// Evict least-recently accessed elements in one pass until the total weight fits in
+    /// `capacity`.
+    /// The tail segment of the list is cut off instead of detaching each node separately, and
+    /// links of evicted nodes are taken so that dropping them does not recurse down the list.
+    fn evict_overweight(&mut self) {
+        self.shed_to(self.capacity);
+    }
+
+    /// Evict least-recently accessed elements until they weigh at most `weight` in total, and
+    /// return how many were evicted.
+    fn shed_to(&mut self, weight: usize) -> usize {
+        if self.weight <= weight {
+            return 0;
+        }
+
+        let mut evicted = 0;
+        let mut tail = self.tail.take();
+        while self.weight > weight {
+            let node = tail.expect("Elements weigh more than 0 in total");
+            self.weight -= self.weigh_node(&node);
+            self.map.remove(&node.as_ref().borrow().key);
+            self.forget_old(&node);
+            if let Some(ghosts) = self.ghosts.as_mut() {
+                ghosts.record(&node.as_ref().borrow().key);
+            }
+            self.emit(CacheEvent::Evicted(&node.as_ref().borrow().key));
+            tail = node.borrow_mut().next.take();
+            evicted += 1;
+        }
+
+        match tail.as_ref() {
+            Some(tail) => tail.borrow_mut().prev = None,
+            None => self.head = None,
+        }
+        self.tail = tail;
+        evicted
+    }
+
     /// Attach `node` to the head of linked list.
     fn attach(&mut self, node: NodeRef<K, V>) {
-        if self.head.is_some() {
-            (*node.borrow_mut()).prev = Some(Rc::downgrade(self.head.as_ref().unwrap()));
-            (*node.borrow_mut()).next = None;
-            (*self.head.as_ref().unwrap().borrow_mut()).next = Some(Rc::clone(&node));
+        if let Some(head) = self.head.as_ref() {
+            node.borrow_mut().prev = Some(Rc::downgrade(head));
+            node.borrow_mut().next = None;
+            head.borrow_mut().next = Some(Rc::clone(&node));
         } else {
             self.tail = Some(Rc::clone(&node));
         }
         self.head = Some(node);
     }
 
+    /// Insert an element of `weight`, which fits in `capacity`, evicting others as needed.
+    fn insert_weighed(&mut self, key: K, value: V, weight: usize) {
+        if let Some(node) = self.map.get(&key).cloned() {
+            let old = mem::replace(&mut node.borrow_mut().value, value);
+            self.weight = self.weight - self.weigh(&key, &old) + weight;
+            self.version += 1;
+            node.borrow_mut().version = self.version;
+            node.borrow_mut().accesses = 0;
+            self.detach(Rc::clone(&node));
+            self.attach(node);
+            self.emit(CacheEvent::Inserted(&key));
+        } else {
+            let key = Rc::new(key);
+            let node = Rc::new(RefCell::new(Node::new(Rc::clone(&key), value)));
+            self.version += 1;
+            node.borrow_mut().version = self.version;
+            self.map.insert(Rc::clone(&key), Rc::clone(&node));
+            if self.midpoint.is_some() {
+                self.attach_old(node);
+            } else {
+                self.attach(node);
+            }
+            self.weight += weight;
+            if let Some(ghosts) = self.ghosts.as_mut() {
+                ghosts.forget(&key);
+            }
+            self.emit(CacheEvent::Inserted(&key));
+        }
+        // The inserted element alone fits, so eviction stops before reaching it.
+        self.evict_overweight();
+        self.rebalance_midpoint();
+    }
+
+    /// Attach `node` to the head of the old segment of midpoint insertion.
+    fn attach_old(&mut self, node: NodeRef<K, V>) {
+        let midpoint = self
+            .midpoint
+            .as_mut()
+            .expect("Midpoint insertion is enabled");
+        let next = match midpoint.old_head.as_ref() {
+            Some(old_head) => {
+                node.borrow_mut().prev = Some(Rc::downgrade(old_head));
+                old_head.borrow_mut().next.replace(Rc::clone(&node))
+            }
+            None => {
+                node.borrow_mut().prev = None;
+                self.tail.replace(Rc::clone(&node))
+            }
+        };
+        match next.as_ref() {
+            Some(next) => next.borrow_mut().prev = Some(Rc::downgrade(&node)),
+            None => self.head = Some(Rc::clone(&node)),
+        }
+        node.borrow_mut().next = next;
+        node.borrow_mut().old = true;
+        midpoint.old_len += 1;
+        midpoint.old_head = Some(node);
+    }
+
+    /// Move the boundary between the young and old segments so that the old one holds its share
+    /// of elements.
+    fn rebalance_midpoint(&mut self) {
+        let len = self.map.len();
+        let Some(midpoint) = self.midpoint.as_mut() else {
+            return;
+        };
+        let target = (len as f64 * midpoint.old_ratio) as usize;
+        while midpoint.old_len < target {
+            let next = match midpoint.old_head.as_ref() {
+                Some(old_head) => old_head.as_ref().borrow().next.clone(),
+                None => self.tail.clone(),
+            };
+            let next = next.expect("Young segment is not empty");
+            next.borrow_mut().old = true;
+            midpoint.old_head = Some(next);
+            midpoint.old_len += 1;
+        }
+        while midpoint.old_len > target {
+            let old_head = midpoint.old_head.take().expect("Old segment is not empty");
+            old_head.borrow_mut().old = false;
+            midpoint.old_head = old_head
+                .as_ref()
+                .borrow()
+                .prev
+                .as_ref()
+                .and_then(Weak::upgrade);
+            midpoint.old_len -= 1;
+        }
+    }
+
+    /// Take `node`, which is being removed from the tail side of the list, out of the old
+    /// segment.
+    fn forget_old(&mut self, node: &NodeRef<K, V>) {
+        if !mem::take(&mut node.borrow_mut().old) {
+            return;
+        }
+        let midpoint = self
+            .midpoint
+            .as_mut()
+            .expect("Midpoint insertion is enabled");
+        midpoint.old_len -= 1;
+        if midpoint.old_len == 0 {
+            midpoint.old_head = None;
+        }
+    }
+
+    fn validate_midpoint(&self) {
+        let Some(midpoint) = self.midpoint.as_ref() else {
+            return;
+        };
+        let mut old_len = 0;
+        let mut current = self.tail.clone();
+        while let Some(node) = current.filter(|node| node.as_ref().borrow().old) {
+            old_len += 1;
+            current = node.as_ref().borrow().next.clone();
+            if current
+                .as_ref()
+                .is_none_or(|next| !next.as_ref().borrow().old)
+            {
+                assert!(
+                    midpoint
+                        .old_head
+                        .as_ref()
+                        .is_some_and(|old_head| Rc::ptr_eq(old_head, &node)),
+                    "`old_head` is not the last old node"
+                );
+            }
+        }
+        assert_eq!(
+            old_len, midpoint.old_len,
+            "`old_len` does not match the old segment"
+        );
+        if old_len == 0 {
+            assert!(midpoint.old_head.is_none(), "`old_head` of empty segment");
+        }
+        assert_eq!(
+            self.map.len() - old_len,
+            self.map
+                .values()
+                .filter(|node| !node.as_ref().borrow().old)
+                .count(),
+            "Old nodes are not contiguous"
+        );
+        assert_eq!(
+            old_len,
+            (self.map.len() as f64 * midpoint.old_ratio) as usize,
+            "Old segment does not hold its share"
+        );
+    }
+
     fn detach(&mut self, node: NodeRef<K, V>) {
+        if mem::take(&mut node.borrow_mut().old) {
+            let midpoint = self
+                .midpoint
+                .as_mut()
+                .expect("Midpoint insertion is enabled");
+            midpoint.old_len -= 1;
+            if midpoint
+                .old_head
+                .as_ref()
+                .is_some_and(|old_head| Rc::ptr_eq(old_head, &node))
+            {
+                midpoint.old_head = node.as_ref().borrow().prev.as_ref().and_then(Weak::upgrade);
+            }
+        }
         match node.as_ref().borrow().prev.as_ref() {
             Some(prev) => match Weak::upgrade(prev) {
                 Some(prev) => {
@@ -95,6 +667,70 @@ where
     }
 }
 
+impl<K, V> SyncNaiveLru<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Receiver of every later change of the contents, e.g. to forward invalidations to a
+    /// message bus. Dropping the receiver unsubscribes.
+    pub fn subscribe_events(&mut self) -> Receiver<CacheEvent<K>>
+    where
+        K: 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        self.listeners.push(Box::new(move |event: CacheEvent<&K>| {
+            sender.send(event.map(K::clone)).is_ok()
+        }));
+        receiver
+    }
+
+    /// Remove the element of `key` on behalf of another cache, without reporting it to
+    /// subscribers, so that an invalidation received from a message bus is not published again.
+    /// Return whether there was such an element.
+    pub fn apply_invalidation(&mut self, key: &K) -> bool {
+        let listeners = mem::take(&mut self.listeners);
+        let removed = self.remove(key).is_some();
+        self.listeners = listeners;
+        removed
+    }
+}
+
+impl<K, V> Resize for SyncNaiveLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        SyncNaiveLru::set_capacity(self, capacity)
+    }
+}
+
+impl<K, V> FromConfig for SyncNaiveLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    fn from_config(config: CacheConfig) -> Self {
+        Self::new(config.capacity)
+    }
+}
+
+impl<K, V, F> FromConfig<RandomState, Weighted<F>> for SyncNaiveLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+    F: Fn(&K, &V) -> usize + 'static,
+{
+    fn from_config(config: CacheConfig<RandomState, Weighted<F>>) -> Self {
+        Self::with_weigher(config.capacity, config.weigher.0)
+    }
+}
+
 impl<K, V> Cache<K, V> for SyncNaiveLru<K, V>
 where
     K: Hash + Eq,
@@ -102,43 +738,188 @@ where
 {
     /// Insert a new key-value pair.
     /// If the number of existing elements is `capacity`, remove least-recently accessed one.
+    /// An element heavier than the whole capacity is not cached, and an existing element of
+    /// `key` is removed so that it does not serve a stale value.
     fn insert(&mut self, key: K, value: V) {
-        let key = Rc::new(key);
-        let node = Rc::new(RefCell::new(Node::new(Rc::clone(&key), value)));
-        self.map.insert(key, Rc::clone(&node));
-        self.attach(node);
+        self.relieve_pressure();
+        let weight = self.weigh(&key, &value);
+        if weight > self.capacity {
+            self.remove(&key);
+            return;
+        }
+        self.insert_weighed(key, value, weight);
+    }
 
-        if self.map.len() == self.capacity + 1 {
-            let tail = self.tail.clone().expect("There must be at least 1 element");
-            self.map.remove(&tail.as_ref().borrow().key);
-            self.detach(tail);
+    /// Same as `insert`, but an element heavier than the whole capacity is rejected by
+    /// `InsertError::TooHeavy`, leaving the cache unchanged.
+    fn try_insert(&mut self, key: K, value: V) -> Result<(), InsertError> {
+        self.relieve_pressure();
+        let weight = self.weigh(&key, &value);
+        if weight > self.capacity {
+            return Err(InsertError::TooHeavy {
+                weight,
+                capacity: self.capacity,
+            });
         }
+        self.insert_weighed(key, value, weight);
+        Ok(())
     }
 
     /// Get clone of a value corresponding to `key`.
     /// This requires mutable reference to `self` because this modifies the order of inner
     /// elements; moves accessed element to head of the list.
-    fn get<Q>(&mut self, key: &Q) -> Option<V>
-    where
-        Rc<K>: Borrow<Q>,
-        Q: Eq + Hash + ?Sized,
-    {
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.relieve_pressure();
         if let Some(node) = self.map.get(key).cloned() {
             self.detach(Rc::clone(&node));
             self.attach(Rc::clone(&node));
+            self.rebalance_midpoint();
+            node.borrow_mut().accesses += 1;
             return Some(node.as_ref().borrow().value.clone());
         }
         None
     }
+
+    /// Get the value of `key` without moving it or counting an access.
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        let node = self.map.get(key)?;
+        let value = node.as_ref().borrow().value.clone();
+        Some(value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let node = self.map.remove(key)?;
+        self.detach(Rc::clone(&node));
+        let node = Rc::try_unwrap(node)
+            .ok()
+            .expect("Detached node is not referenced from the list")
+            .into_inner();
+        self.emit(CacheEvent::Removed(&node.key));
+        self.weight -= self.weigh(&node.key, &node.value);
+        self.rebalance_midpoint();
+        Some(node.value)
+    }
+
+    fn clear(&mut self) {
+        if !self.listeners.is_empty() {
+            let keys = self.map.keys().cloned().collect::<Vec<_>>();
+            keys.iter()
+                .for_each(|key| self.emit(CacheEvent::Removed(key)));
+        }
+        self.map.clear();
+        self.unlink_all();
+        self.weight = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+impl<K, V> SyncNaiveLru<K, V> {
+    /// Report `event` to subscribers, forgetting those which are gone.
+    fn emit(&mut self, event: CacheEvent<&K>) {
+        self.listeners.retain_mut(|listener| listener(event));
+    }
+
+    /// Unlink nodes one by one from the tail.
+    /// Dropping the list as is would recurse through `next` once per element and overflow the
+    /// stack for large caches.
+    fn unlink_all(&mut self) {
+        if let Some(midpoint) = self.midpoint.as_mut() {
+            midpoint.old_head = None;
+            midpoint.old_len = 0;
+        }
+        self.head = None;
+        let mut current = self.tail.take();
+        while let Some(node) = current {
+            current = node.borrow_mut().next.take();
+        }
+    }
+}
+
+/// Cache of `DEFAULT_CAPACITY`.
+impl<K, V> Default for SyncNaiveLru<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// Caches are equal when they have the same capacity and the same elements in the same recency
+/// order. Weighers and midpoint insertion are not compared.
+impl<K: PartialEq, V: PartialEq> PartialEq for SyncNaiveLru<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.capacity != other.capacity || self.map.len() != other.map.len() {
+            return false;
+        }
+        let mut current = (self.tail.clone(), other.tail.clone());
+        while let (Some(node), Some(other_node)) = current {
+            let (node, other_node) = (node.as_ref().borrow(), other_node.as_ref().borrow());
+            if node.key != other_node.key || node.value != other_node.value {
+                return false;
+            }
+            current = (node.next.clone(), other_node.next.clone());
+        }
+        true
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for SyncNaiveLru<K, V> {}
+
+impl<K, V> Drop for SyncNaiveLru<K, V> {
+    fn drop(&mut self) {
+        self.unlink_all();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::adaptive::{AdaptiveCache, AdaptiveConfig};
+    use common::{model::Op, replay, rng::Rng, CacheBuilder, InstrumentedCache};
+    use std::cell::Cell;
+
+    common::cache_conformance_tests!(SyncNaiveLru::new);
+
+    proptest::proptest! {
+        #[test]
+        fn matches_reference_model(capacity in 0usize..8, ops in common::model::operations(16)) {
+            common::model::check_against_reference(SyncNaiveLru::new(capacity), capacity, &ops);
+        }
+
+        #[test]
+        fn midpoint_insertion_keeps_invariants(
+            capacity in 0usize..8,
+            insertion_point in 0.0..=1.0,
+            ops in common::model::operations(16),
+        ) {
+            let mut lru = SyncNaiveLru::new(capacity);
+            lru.set_midpoint_insertion(Some(insertion_point));
+            for op in ops {
+                match op {
+                    Op::Insert(key, value) => lru.insert(key, value),
+                    Op::Get(key) => drop(lru.get(&key)),
+                    Op::Remove(key) => drop(lru.remove(&key)),
+                    Op::Clear => lru.clear(),
+                }
+                lru.validate();
+            }
+            lru.set_capacity(capacity / 2);
+            lru.validate();
+            lru.split_off_cold(1).validate();
+            lru.validate();
+            lru.set_midpoint_insertion(None);
+            lru.validate();
+        }
+    }
 
     fn setup_lru_with_capacity_3() -> SyncNaiveLru<i32, i32> {
         let mut lru = SyncNaiveLru::new(3);
-        vec![(1, 2), (3, 4), (5, 6)]
+        [(1, 2), (3, 4), (5, 6)]
             .iter()
             .for_each(|kv| lru.insert(kv.0, kv.1));
         lru
@@ -157,7 +938,7 @@ mod tests {
         );
         assert_eq!(
             lru.into_iter().collect::<Vec<_>>(),
-            vec![(1, 2), (3, 4), (5, 6)]
+            [(1, 2), (3, 4), (5, 6)]
         );
     }
 
@@ -188,7 +969,7 @@ mod tests {
     #[test]
     fn exceeding_insert() {
         let mut lru = SyncNaiveLru::new(3);
-        let expected = vec![(1, 2), (3, 4), (5, 6), (7, 8)];
+        let expected = [(1, 2), (3, 4), (5, 6), (7, 8)];
         expected.iter().for_each(|kv| lru.insert(kv.0, kv.1));
 
         assert_eq!(lru.get(&1), None);
@@ -198,6 +979,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_after_operations() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.validate();
+        lru.get(&1);
+        lru.insert(7, 8);
+        lru.remove(&5);
+        lru.validate();
+        lru.set_capacity(1);
+        lru.validate();
+        lru.clear();
+        lru.validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "Weight exceeds capacity")]
+    fn validate_detects_broken_capacity() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.capacity = 2;
+        lru.validate();
+    }
+
+    #[test]
+    fn shrink_capacity() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.set_capacity(1);
+        assert_eq!(lru.capacity(), 1);
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(lru.get(&3), None);
+        assert_eq!(lru.into_iter().collect::<Vec<_>>(), vec![(5, 6)]);
+    }
+
+    #[test]
+    fn shrink_capacity_to_zero() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.set_capacity(0);
+        assert!(lru.head.is_none());
+        assert_eq!(lru.get(&5), None);
+        assert_eq!(lru.into_iter().collect::<Vec<_>>(), vec![]);
+    }
+
+    #[test]
+    fn grow_capacity() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.set_capacity(4);
+        lru.insert(7, 8);
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(1, 2), (3, 4), (5, 6), (7, 8)]
+        );
+    }
+
+    #[test]
+    fn insert_after_shrink() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.set_capacity(2);
+        lru.insert(7, 8);
+        assert_eq!(lru.into_iter().collect::<Vec<_>>(), vec![(5, 6), (7, 8)]);
+    }
+
+    #[test]
+    fn insert_existing_key_updates_value() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.insert(1, 10);
+        lru.insert(7, 8);
+        assert_eq!(lru.get(&1), Some(10));
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(5, 6), (7, 8), (1, 10)]
+        );
+    }
+
+    #[test]
+    fn drop_large_cache() {
+        let mut lru = SyncNaiveLru::new(100_000);
+        (0..100_000).for_each(|i| lru.insert(i, i));
+        drop(lru);
+    }
+
     #[test]
     fn get_reorders_entry() {
         let mut lru = setup_lru_with_capacity_3();
@@ -207,4 +1067,646 @@ mod tests {
             vec![(1, 2), (5, 6), (3, 4)]
         );
     }
+
+    #[test]
+    fn into_iter_moves_keys() {
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        struct Key(i32);
+
+        let mut lru = SyncNaiveLru::new(2);
+        lru.insert(Key(1), 10);
+        lru.insert(Key(2), 20);
+        lru.insert(Key(3), 30);
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(Key(2), 20), (Key(3), 30)]
+        );
+    }
+
+    /// Value counting its live instances, to see that nodes are freed rather than kept alive by
+    /// a strong cycle in the list.
+    struct Counted(Rc<Cell<usize>>);
+
+    impl Counted {
+        fn new(live: &Rc<Cell<usize>>) -> Self {
+            live.set(live.get() + 1);
+            Self(Rc::clone(live))
+        }
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            Self::new(&self.0)
+        }
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() - 1);
+        }
+    }
+
+    fn setup_counted_lru(live: &Rc<Cell<usize>>) -> SyncNaiveLru<i32, Counted> {
+        let mut lru = SyncNaiveLru::new(3);
+        (0..3).for_each(|i| lru.insert(i, Counted::new(live)));
+        lru
+    }
+
+    #[test]
+    fn drop_evicted_value() {
+        let live = Rc::new(Cell::new(0));
+        let mut lru = setup_counted_lru(&live);
+        lru.insert(3, Counted::new(&live));
+        assert_eq!(live.get(), 3);
+        lru.set_capacity(1);
+        assert_eq!(live.get(), 1);
+    }
+
+    #[test]
+    fn drop_removed_value() {
+        let live = Rc::new(Cell::new(0));
+        let mut lru = setup_counted_lru(&live);
+        drop(lru.remove(&1));
+        assert_eq!(live.get(), 2);
+        // Removing the head and tail unlinks them as well.
+        drop(lru.remove(&2));
+        drop(lru.remove(&0));
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn drop_overwritten_value() {
+        let live = Rc::new(Cell::new(0));
+        let mut lru = setup_counted_lru(&live);
+        lru.insert(1, Counted::new(&live));
+        assert_eq!(live.get(), 3);
+    }
+
+    #[test]
+    fn drop_cleared_values() {
+        let live = Rc::new(Cell::new(0));
+        let mut lru = setup_counted_lru(&live);
+        lru.clear();
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn drop_values_with_cache() {
+        let live = Rc::new(Cell::new(0));
+        let mut lru = setup_counted_lru(&live);
+        // Reorder nodes so that links other than the initial ones are dropped.
+        drop(lru.get(&0));
+        drop(lru.get(&1));
+        drop(lru);
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn drop_partially_consumed_iter() {
+        let live = Rc::new(Cell::new(0));
+        let mut iter = setup_counted_lru(&live).into_iter();
+        drop(iter.next());
+        assert_eq!(live.get(), 2);
+        drop(iter);
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn build_from_builder() {
+        let mut lru = CacheBuilder::new()
+            .capacity(1)
+            .build::<SyncNaiveLru<i32, i32>>()
+            .unwrap();
+        lru.insert(1, 2);
+        lru.insert(3, 4);
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(lru.get(&3), Some(4));
+    }
+
+    #[test]
+    fn build_weighted_from_builder() {
+        let mut lru = CacheBuilder::new()
+            .capacity(5)
+            .weigher(|_: &i32, value: &String| value.len())
+            .build::<SyncNaiveLru<_, _>>()
+            .unwrap();
+        lru.insert(1, "aaa".to_string());
+        lru.insert(2, "bbb".to_string());
+        assert_eq!(lru.weight(), 3);
+        assert_eq!(lru.get(&1), None);
+        let ttl = CacheBuilder::new()
+            .capacity(5)
+            .ttl(std::time::Duration::from_secs(1))
+            .build::<SyncNaiveLru<i32, i32>>();
+        assert_eq!(ttl.err(), Some(common::builder::BuildError::UnsupportedTtl));
+    }
+
+    fn setup_weighted_lru() -> SyncNaiveLru<i32, String> {
+        let mut lru = SyncNaiveLru::with_weigher(10, |_, value: &String| value.len());
+        lru.insert(1, "aaaa".to_string());
+        lru.insert(2, "bbb".to_string());
+        lru.insert(3, "cc".to_string());
+        lru
+    }
+
+    #[test]
+    fn evict_by_weight() {
+        let mut lru = setup_weighted_lru();
+        assert_eq!(lru.weight(), 9);
+        lru.insert(4, "dddddd".to_string());
+        assert_eq!(lru.weight(), 8);
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(lru.get(&2), None);
+        lru.validate();
+    }
+
+    #[test]
+    fn charge_keys() {
+        let mut lru =
+            SyncNaiveLru::with_weigher(10, |key: &String, value: &String| key.len() + value.len());
+        lru.insert("long key".to_string(), "a".to_string());
+        assert_eq!(lru.weight(), 9);
+        lru.insert("k".to_string(), "bb".to_string());
+        assert_eq!(lru.weight(), 3);
+        assert_eq!(lru.get(&"long key".to_string()), None);
+        assert_eq!(lru.remove(&"k".to_string()), Some("bb".to_string()));
+        assert_eq!(lru.weight(), 0);
+    }
+
+    #[test]
+    fn heap_weigher_counts_keys() {
+        use std::mem::size_of;
+
+        let entry = size_of::<String>() + size_of::<u32>();
+        let mut lru = SyncNaiveLru::with_heap_weigher(2 * entry + 8);
+        lru.insert(String::from("abcd"), 1u32);
+        lru.insert(String::from("efgh"), 2);
+        assert_eq!(lru.weight(), 2 * entry + 8);
+        lru.insert(String::from("i"), 3);
+        assert_eq!(lru.len(), 2);
+        assert_eq!(lru.weight(), 2 * entry + 5);
+        lru.validate();
+    }
+
+    #[test]
+    fn update_changes_weight() {
+        let mut lru = setup_weighted_lru();
+        lru.insert(3, "ccccc".to_string());
+        assert_eq!(lru.weight(), 8);
+        assert_eq!(lru.get(&1), None);
+        lru.remove(&3);
+        assert_eq!(lru.weight(), 3);
+        lru.validate();
+    }
+
+    #[test]
+    fn try_insert_too_heavy() {
+        let mut lru = setup_weighted_lru();
+        assert_eq!(
+            lru.try_insert(1, "x".repeat(11)),
+            Err(InsertError::TooHeavy {
+                weight: 11,
+                capacity: 10
+            })
+        );
+        assert_eq!(lru.len(), 3);
+        assert_eq!(lru.get(&1), Some("aaaa".to_string()));
+        assert_eq!(lru.try_insert(4, "x".repeat(10)), Ok(()));
+        assert_eq!(lru.len(), 1);
+    }
+
+    #[test]
+    fn insert_too_heavy_removes_stale_value() {
+        let mut lru = setup_weighted_lru();
+        lru.insert(1, "x".repeat(11));
+        assert_eq!(lru.get(&1), None);
+        assert_eq!(lru.weight(), 5);
+        lru.validate();
+    }
+
+    #[test]
+    fn shrink_weighted_capacity() {
+        let mut lru = setup_weighted_lru();
+        lru.set_capacity(5);
+        assert_eq!(lru.weight(), 5);
+        assert_eq!(lru.get(&1), None);
+        lru.validate();
+    }
+
+    #[test]
+    fn retain_keeps_order() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.retain(|key, _| *key != 3);
+        lru.validate();
+        assert_eq!(lru.into_iter().collect::<Vec<_>>(), vec![(1, 2), (5, 6)]);
+    }
+
+    #[test]
+    fn invalidate_all_matching_keys() {
+        let mut lru = SyncNaiveLru::new(4);
+        for key in ["a/1", "b/1", "a/2", "b/2"] {
+            lru.insert(key, key.len());
+        }
+        lru.set_midpoint_insertion(Some(0.5));
+        assert_eq!(lru.invalidate_all_matching(|key| key.starts_with("a/")), 2);
+        lru.validate();
+        assert_eq!(lru.invalidate_all_matching(|key| key.starts_with("c/")), 0);
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![("b/1", 3), ("b/2", 3)]
+        );
+    }
+
+    #[test]
+    fn merge_other_as_more_recent() {
+        let mut lru = SyncNaiveLru::new(4);
+        lru.insert(1, 2);
+        lru.insert(3, 4);
+        let mut other = SyncNaiveLru::new(3);
+        other.insert(5, 6);
+        other.insert(3, 40);
+        lru.merge(other);
+        lru.validate();
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(1, 2), (5, 6), (3, 40)]
+        );
+    }
+
+    #[test]
+    fn merge_evicts_least_recent() {
+        let mut lru = setup_lru_with_capacity_3();
+        let mut other = SyncNaiveLru::new(2);
+        other.insert(7, 8);
+        other.insert(9, 10);
+        lru.merge(other);
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(5, 6), (7, 8), (9, 10)]
+        );
+    }
+
+    #[test]
+    fn split_off_cold_entries() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.insert(7, 8);
+        let cold = lru.split_off_cold(2);
+        lru.validate();
+        cold.validate();
+        assert_eq!(cold.capacity(), 3);
+        assert_eq!(cold.into_iter().collect::<Vec<_>>(), vec![(3, 4), (5, 6)]);
+        assert_eq!(lru.into_iter().collect::<Vec<_>>(), vec![(7, 8)]);
+    }
+
+    #[test]
+    fn split_off_cold_more_than_len() {
+        let mut lru = setup_lru_with_capacity_3();
+        let cold = lru.split_off_cold(5);
+        lru.validate();
+        assert!(lru.is_empty());
+        lru.insert(9, 10);
+        assert_eq!(lru.get(&9), Some(10));
+        assert_eq!(cold.len(), 3);
+        assert!(lru.split_off_cold(0).is_empty());
+    }
+
+    #[test]
+    fn split_off_cold_keeps_versions_unique() {
+        let mut lru = setup_lru_with_capacity_3();
+        let mut cold = lru.split_off_cold(1);
+        let (_, version) = cold.get_versioned(&1).unwrap();
+        cold.insert(1, 20);
+        assert_eq!(
+            cold.insert_if_version(1, 30, version),
+            Err(InsertError::VersionMismatch {
+                expected: version,
+                actual: Some(cold.get_versioned(&1).unwrap().1),
+            })
+        );
+        assert_ne!(cold.get_versioned(&1).unwrap().1, version);
+    }
+
+    #[test]
+    fn split_off_cold_is_not_reported() {
+        let mut lru = setup_lru_with_capacity_3();
+        let events = lru.subscribe_events();
+        let cold = lru.split_off_cold(2);
+        assert_eq!(cold.len(), 2);
+        assert_eq!(events.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn split_off_cold_moves_weight() {
+        let mut lru = setup_weighted_lru();
+        let cold = lru.split_off_cold(1);
+        assert_eq!(cold.weight(), 4);
+        assert_eq!(lru.weight(), 5);
+        lru.validate();
+        cold.validate();
+    }
+
+    #[test]
+    fn freeze_keeps_elements() {
+        let frozen = setup_lru_with_capacity_3().freeze();
+        assert_eq!(frozen.len(), 3);
+        assert_eq!(frozen.get(&3), Some(&4));
+        assert_eq!(frozen.get(&7), None);
+    }
+
+    #[test]
+    fn adaptive_capacity() {
+        let config = AdaptiveConfig {
+            min_capacity: 1,
+            max_capacity: 64,
+            target_hit_ratio: 0.9,
+            tolerance: 0.1,
+            interval: 100,
+            step: 8,
+        };
+        let mut cache = AdaptiveCache::new(SyncNaiveLru::new(1), config);
+        for i in 0..2000 {
+            if cache.get(&(i % 20)).is_none() {
+                cache.insert(i % 20, i);
+            }
+        }
+        assert_eq!(cache.capacity(), 25);
+        cache.into_inner().validate();
+    }
+
+    #[test]
+    fn adaptive_counts_rejected_inserts_alike() {
+        let lru = SyncNaiveLru::with_weigher(4, |_, value: &usize| *value);
+        let config = AdaptiveConfig {
+            min_capacity: 4,
+            max_capacity: 4,
+            target_hit_ratio: 0.9,
+            tolerance: 0.1,
+            interval: 100,
+            step: 1,
+        };
+        let mut cache = AdaptiveCache::new(lru, config);
+        cache.insert(1, 10);
+        assert!(cache.try_insert(2, 10).is_err());
+        assert_eq!(cache.stats().inserts, 2);
+        assert!(cache.is_empty());
+    }
+
+    /// Count hits on 6 hot keys looked up between scans of 10 keys accessed only once.
+    fn hot_hits_between_scans(insertion_point: Option<f64>) -> usize {
+        let mut lru = SyncNaiveLru::new(8);
+        lru.set_midpoint_insertion(insertion_point);
+        let mut hits = 0;
+        for round in 0..10 {
+            for key in 0..6 {
+                if lru.get(&key).is_some() {
+                    hits += 1;
+                } else {
+                    lru.insert(key, key);
+                }
+            }
+            for key in 0..10 {
+                lru.insert(100 + round * 10 + key, key);
+            }
+        }
+        lru.validate();
+        hits
+    }
+
+    #[test]
+    fn midpoint_insertion_resists_scan() {
+        assert_eq!(hot_hits_between_scans(None), 0);
+        // Once the hot keys are accessed twice, they stay in the young segment.
+        assert!(hot_hits_between_scans(Some(0.75)) >= 6 * 7);
+    }
+
+    #[test]
+    fn midpoint_insertion_enters_behind_young() {
+        let mut lru = setup_lru_with_capacity_3();
+        lru.set_midpoint_insertion(Some(0.5));
+        lru.insert(7, 8);
+        lru.validate();
+        // 7 enters the old segment next to 1, which is then evicted.
+        assert_eq!(
+            lru.into_iter().collect::<Vec<_>>(),
+            vec![(7, 8), (3, 4), (5, 6)]
+        );
+    }
+
+    #[test]
+    fn insert_if_version_detects_changes() {
+        let mut lru = setup_lru_with_capacity_3();
+        let (value, version) = lru.get_versioned(&1).unwrap();
+        assert_eq!(value, 2);
+        let next = lru.insert_if_version(1, value + 1, version).unwrap();
+        assert_ne!(next, version);
+        assert_eq!(
+            lru.insert_if_version(1, value + 2, version),
+            Err(InsertError::VersionMismatch {
+                expected: version,
+                actual: Some(next)
+            })
+        );
+        assert_eq!(lru.get(&1), Some(3));
+    }
+
+    #[test]
+    fn insert_if_version_after_remove() {
+        let mut lru = setup_lru_with_capacity_3();
+        let (_, version) = lru.get_versioned(&3).unwrap();
+        lru.remove(&3);
+        assert_eq!(
+            lru.insert_if_version(3, 5, version),
+            Err(InsertError::VersionMismatch {
+                expected: version,
+                actual: None
+            })
+        );
+        // A reinserted element gets a new version, too.
+        lru.insert(3, 4);
+        assert_ne!(lru.get_versioned(&3).unwrap().1, version);
+        assert_eq!(lru.get_versioned(&7), None);
+    }
+
+    #[test]
+    fn evict_to_fraction() {
+        let mut lru = SyncNaiveLru::new(4);
+        (0..4).for_each(|key| lru.insert(key, key));
+        assert_eq!(lru.evict_to(0.5), 2);
+        lru.validate();
+        assert_eq!(lru.into_iter().collect::<Vec<_>>(), vec![(2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn evict_bytes_by_weight() {
+        let mut lru = SyncNaiveLru::with_weigher(100, |_, value: &Vec<u8>| value.len());
+        lru.insert(1, vec![0; 10]);
+        lru.insert(2, vec![0; 30]);
+        lru.insert(3, vec![0; 20]);
+        assert_eq!(lru.evict_bytes(15), 2);
+        assert_eq!(lru.weight(), 20);
+        assert_eq!(lru.evict_bytes(100), 1);
+        assert!(lru.is_empty());
+        lru.validate();
+    }
+
+    #[test]
+    fn shed_on_pressure() {
+        let mut lru = SyncNaiveLru::new(10);
+        (0..10).for_each(|key| lru.insert(key, key));
+        let handle = lru.pressure_handle();
+        handle.shed(0.3);
+        assert_eq!(lru.len(), 10);
+        assert_eq!(lru.get(&0), None);
+        assert_eq!(lru.len(), 7);
+        assert_eq!(lru.get(&3), Some(3));
+        lru.validate();
+    }
+
+    #[test]
+    fn idle_cache_keeps_elements_under_pressure() {
+        let mut lru = SyncNaiveLru::new(10);
+        (0..10).for_each(|key| lru.insert(key, key));
+        lru.pressure_handle().shed(0.5);
+        assert_eq!(lru.len(), 10);
+        assert_eq!(lru.relieve_pressure(), 5);
+        assert_eq!(lru.len(), 5);
+        assert_eq!(lru.relieve_pressure(), 0);
+        lru.validate();
+    }
+
+    #[test]
+    fn rank_from_most_recent() {
+        let mut lru = setup_lru_with_capacity_3();
+        assert_eq!(lru.rank_of(&5), Some(0));
+        assert_eq!(lru.rank_of(&1), Some(2));
+        assert_eq!(lru.rank_of(&7), None);
+        // Querying does not promote.
+        assert_eq!(lru.rank_of(&1), Some(2));
+        lru.get(&1);
+        assert_eq!(lru.rank_of(&1), Some(0));
+        assert_eq!(lru.rank_of(&3), Some(2));
+    }
+
+    #[test]
+    fn default_capacity() {
+        #[derive(Default)]
+        struct Service {
+            cache: SyncNaiveLru<u64, String>,
+        }
+
+        let service = Service::default();
+        assert_eq!(service.cache.capacity(), DEFAULT_CAPACITY);
+        assert!(service.cache.is_empty());
+    }
+
+    #[test]
+    fn equal_in_recency_order() {
+        let mut lru = setup_lru_with_capacity_3();
+        let mut other = SyncNaiveLru::new(3);
+        other.insert(3, 4);
+        other.insert(1, 2);
+        other.insert(5, 6);
+        assert!(lru != other);
+        lru.get(&1);
+        other.get(&1);
+        assert!(lru == other);
+        other.set_capacity(4);
+        assert!(lru != other);
+    }
+
+    #[test]
+    fn subscribe_to_events() {
+        let mut lru = SyncNaiveLru::new(2);
+        let events = lru.subscribe_events();
+        lru.insert(1, 10);
+        lru.insert(2, 20);
+        lru.insert(1, 11);
+        lru.insert(3, 30);
+        lru.remove(&1);
+        lru.clear();
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            vec![
+                CacheEvent::Inserted(1),
+                CacheEvent::Inserted(2),
+                CacheEvent::Inserted(1),
+                CacheEvent::Inserted(3),
+                CacheEvent::Evicted(2),
+                CacheEvent::Removed(1),
+                CacheEvent::Removed(3),
+            ]
+        );
+        drop(events);
+        lru.insert(4, 40);
+        assert!(lru.listeners.is_empty());
+    }
+
+    #[test]
+    fn apply_invalidation_is_not_reported() {
+        let mut lru = setup_lru_with_capacity_3();
+        let events = lru.subscribe_events();
+        assert!(lru.apply_invalidation(&3));
+        assert!(!lru.apply_invalidation(&3));
+        lru.remove(&1);
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            vec![CacheEvent::Removed(1)]
+        );
+        assert_eq!(lru.len(), 1);
+    }
+
+    #[test]
+    fn remember_evicted_keys() {
+        let mut lru = setup_lru_with_capacity_3();
+        assert!(!lru.was_recently_evicted(&1));
+        lru.set_ghost_history(Some(2));
+        lru.insert(7, 8);
+        lru.insert(9, 10);
+        lru.remove(&5);
+        assert!(lru.was_recently_evicted(&1));
+        assert!(lru.was_recently_evicted(&3));
+        assert!(!lru.was_recently_evicted(&5));
+        lru.insert(1, 2);
+        assert!(!lru.was_recently_evicted(&1));
+        lru.set_ghost_history(None);
+        assert!(!lru.was_recently_evicted(&3));
+    }
+
+    /// Hits and misses of lookups in `recorded_traffic` when this implementation was captured as
+    /// the baseline.
+    const BASELINE: &str = "MMMMHMHMMMHMHHMMMHMHHMMMMHHMHHMMMMHHMHHHHHHHHHHH\
+        HMHMHMMHHMMMMHMMHHMMHMHHHHHMHMHHHHMHMHMHMH";
+
+    /// Traffic on 8 keys, where lower keys are accessed more often.
+    fn recorded_traffic() -> Vec<Op<u64, u64>> {
+        let mut rng = Rng::new(7);
+        (0..200)
+            .map(|i| {
+                let bound = rng.below(8) + 1;
+                let key = rng.below(bound);
+                match rng.below(10) {
+                    0..=3 => Op::Insert(key, i),
+                    4..=8 => Op::Get(key),
+                    _ => Op::Remove(key),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replay_rejected_try_insert() {
+        let make = || SyncNaiveLru::with_weigher(10, |_, value: &String| value.len());
+        let mut cache = InstrumentedCache::with_op_log(make());
+        cache.insert(1, "aaaa".to_string());
+        assert!(cache.try_insert(1, "b".repeat(20)).is_err());
+        assert_eq!(cache.get(&1), Some("aaaa".to_string()));
+        let log = cache.take_op_log().unwrap();
+        replay::check_baseline(&log, &mut make(), "H");
+    }
+
+    #[test]
+    fn matches_recorded_baseline() {
+        let log = recorded_traffic();
+        replay::check_baseline(&log, &mut SyncNaiveLru::new(4), BASELINE);
+    }
 }
@@ -0,0 +1,136 @@
+use crate::SyncNaiveLru;
+use common::{Cache, CacheStats};
+use std::{collections::HashMap, hash::Hash};
+
+/// LRU cache shared by several namespaces, e.g. tenants of a service.
+/// Every namespace competes for one capacity budget and elements are evicted in the same LRU
+/// order regardless of their namespace, but stats are kept per namespace and one namespace can be
+/// invalidated without touching the others.
+pub struct PartitionedCache<N, K, V> {
+    lru: SyncNaiveLru<(N, K), V>,
+    stats: HashMap<N, CacheStats>,
+}
+
+impl<N, K, V> PartitionedCache<N, K, V>
+where
+    N: Hash + Eq + Clone,
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lru: SyncNaiveLru::new(capacity),
+            stats: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, namespace: N, key: K, value: V) {
+        self.stats.entry(namespace.clone()).or_default().inserts += 1;
+        self.lru.insert((namespace, key), value);
+    }
+
+    /// Lookups in a namespace never inserted into are not counted, so that looking up arbitrary
+    /// namespaces does not grow the stats.
+    pub fn get(&mut self, namespace: &N, key: &K) -> Option<V> {
+        let value = self.lru.get(&(namespace.clone(), key.clone()));
+        if let Some(stats) = self.stats.get_mut(namespace) {
+            if value.is_some() {
+                stats.hits += 1;
+            } else {
+                stats.misses += 1;
+            }
+        }
+        value
+    }
+
+    pub fn remove(&mut self, namespace: &N, key: &K) -> Option<V> {
+        self.lru.remove(&(namespace.clone(), key.clone()))
+    }
+
+    /// Remove every element of `namespace` and return how many were removed.
+    /// Stats of the namespace are kept.
+    pub fn invalidate_namespace(&mut self, namespace: &N) -> usize {
+        let len = self.lru.len();
+        self.lru.retain(|(n, _), _| n != namespace);
+        len - self.lru.len()
+    }
+
+    /// Stats of accesses to `namespace`, all zero if it has never been inserted into.
+    pub fn stats(&self, namespace: &N) -> CacheStats {
+        self.stats.get(namespace).copied().unwrap_or_default()
+    }
+
+    /// Number of elements of every namespace.
+    pub fn len(&self) -> usize {
+        self.lru.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lru.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.lru.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_cache() -> PartitionedCache<&'static str, i32, i32> {
+        let mut cache = PartitionedCache::new(4);
+        cache.insert("a", 1, 10);
+        cache.insert("b", 1, 20);
+        cache.insert("a", 2, 30);
+        cache
+    }
+
+    #[test]
+    fn namespaces_do_not_collide() {
+        let mut cache = setup_cache();
+        assert_eq!(cache.get(&"a", &1), Some(10));
+        assert_eq!(cache.get(&"b", &1), Some(20));
+        assert_eq!(cache.get(&"b", &2), None);
+    }
+
+    #[test]
+    fn share_capacity() {
+        let mut cache = setup_cache();
+        cache.insert("b", 2, 40);
+        cache.insert("b", 3, 50);
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.get(&"a", &1), None);
+        assert_eq!(cache.get(&"a", &2), Some(30));
+    }
+
+    #[test]
+    fn stats_per_namespace() {
+        let mut cache = setup_cache();
+        cache.get(&"a", &1);
+        cache.get(&"a", &3);
+        cache.get(&"b", &1);
+        assert_eq!(
+            cache.stats(&"a"),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                inserts: 2
+            }
+        );
+        assert_eq!(cache.stats(&"b").hits, 1);
+        cache.get(&"c", &1);
+        assert_eq!(cache.stats(&"c"), CacheStats::default());
+        assert_eq!(cache.stats.len(), 2);
+    }
+
+    #[test]
+    fn invalidate_one_namespace() {
+        let mut cache = setup_cache();
+        assert_eq!(cache.invalidate_namespace(&"a"), 2);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&"a", &2), None);
+        assert_eq!(cache.get(&"b", &1), Some(20));
+        assert_eq!(cache.invalidate_namespace(&"c"), 0);
+    }
+}
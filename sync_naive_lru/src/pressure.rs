@@ -0,0 +1,75 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Handle through which code outside of the cache, e.g. an allocator or cgroup watcher, asks a
+/// `SyncNaiveLru` to shed its coldest elements.
+/// The cache cannot be shared between threads, so the handle only records the request and the
+/// cache applies it on its next `get`, `insert` or `try_insert`. Shedding is therefore lazy: an
+/// idle cache keeps its elements until its owner calls `SyncNaiveLru::relieve_pressure`, e.g.
+/// from a periodic tick. The handle itself can be sent anywhere.
+#[derive(Clone, Debug)]
+pub struct PressureHandle {
+    /// Bits of the share of weight to keep, which is 1 without pending requests.
+    keep: Arc<AtomicU64>,
+}
+
+impl PressureHandle {
+    pub(crate) fn new() -> Self {
+        Self {
+            keep: Arc::new(AtomicU64::new(1.0f64.to_bits())),
+        }
+    }
+
+    /// Ask the cache to evict `fraction` of its weight, coldest elements first.
+    /// Requests made before the cache applies them are not added up; the largest one wins.
+    pub fn shed(&self, fraction: f64) {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "Fraction must be in [0, 1]"
+        );
+        let keep = 1.0 - fraction;
+        let _ = self
+            .keep
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                (keep < f64::from_bits(bits)).then(|| keep.to_bits())
+            });
+    }
+
+    /// Callback calling `shed`, to be registered with a watcher taking one.
+    pub fn callback(&self) -> impl Fn(f64) + Send + Sync + 'static {
+        let handle = self.clone();
+        move |fraction| handle.shed(fraction)
+    }
+
+    /// Take the share of weight to keep requested so far, if any.
+    pub(crate) fn take(&self) -> Option<f64> {
+        let keep = f64::from_bits(self.keep.swap(1.0f64.to_bits(), Ordering::Relaxed));
+        (keep < 1.0).then_some(keep)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_request_wins() {
+        let handle = PressureHandle::new();
+        assert_eq!(handle.take(), None);
+        handle.shed(0.25);
+        handle.shed(0.5);
+        handle.shed(0.1);
+        assert_eq!(handle.take(), Some(0.5));
+        assert_eq!(handle.take(), None);
+    }
+
+    #[test]
+    fn shed_from_another_thread() {
+        let handle = PressureHandle::new();
+        let callback = handle.callback();
+        std::thread::spawn(move || callback(0.75)).join().unwrap();
+        assert_eq!(handle.take(), Some(0.25));
+    }
+}
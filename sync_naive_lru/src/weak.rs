@@ -0,0 +1,100 @@
+use crate::SyncNaiveLru;
+use common::Cache;
+use std::{
+    hash::Hash,
+    sync::{Arc, Weak},
+};
+
+/// LRU cache holding values owned elsewhere by `Arc`, without keeping them alive.
+/// An element becomes dead once the last strong reference to its value is dropped: lookups
+/// return `None` for it and remove it, and `purge_dead` removes every dead element at once.
+/// Dead elements not purged yet still take room, so call `purge_dead` as periodic maintenance
+/// when values are dropped often.
+pub struct WeakValueLru<K, V> {
+    lru: SyncNaiveLru<K, Weak<V>>,
+}
+
+impl<K: Hash + Eq, V> WeakValueLru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lru: SyncNaiveLru::new(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.lru.capacity()
+    }
+
+    /// Remove every dead element and return how many were removed.
+    pub fn purge_dead(&mut self) -> usize {
+        let len = self.lru.len();
+        self.lru.retain(|_, value| value.strong_count() > 0);
+        len - self.lru.len()
+    }
+}
+
+impl<K: Hash + Eq, V> Cache<K, Arc<V>> for WeakValueLru<K, V> {
+    /// Insert a weak reference to `value`; the cache does not keep the value alive.
+    fn insert(&mut self, key: K, value: Arc<V>) {
+        self.lru.insert(key, Arc::downgrade(&value));
+    }
+
+    /// Get the value of `key` if it is still alive, removing the element otherwise.
+    fn get(&mut self, key: &K) -> Option<Arc<V>> {
+        let value = self.lru.get(key)?.upgrade();
+        if value.is_none() {
+            self.lru.remove(key);
+        }
+        value
+    }
+
+    /// Get the value of `key` if it is still alive, leaving a dead element in place.
+    fn get_quiet(&self, key: &K) -> Option<Arc<V>> {
+        self.lru.get_quiet(key)?.upgrade()
+    }
+
+    fn remove(&mut self, key: &K) -> Option<Arc<V>> {
+        self.lru.remove(key)?.upgrade()
+    }
+
+    fn clear(&mut self) {
+        self.lru.clear();
+    }
+
+    /// Number of elements, including dead ones which have not been removed yet.
+    fn len(&self) -> usize {
+        self.lru.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_with_last_strong_reference() {
+        let mut lru = WeakValueLru::new(3);
+        let alive = Arc::new(String::from("alive"));
+        lru.insert(1, Arc::clone(&alive));
+        lru.insert(2, Arc::new(String::from("dropped")));
+        assert_eq!(lru.len(), 2);
+        assert_eq!(lru.get(&1), Some(alive));
+        assert_eq!(lru.get(&2), None);
+        assert_eq!(lru.len(), 1);
+    }
+
+    #[test]
+    fn purge_dead_elements() {
+        let mut lru = WeakValueLru::new(4);
+        let mut values = (0..4).map(Arc::new).collect::<Vec<_>>();
+        values
+            .iter()
+            .enumerate()
+            .for_each(|(key, value)| lru.insert(key, Arc::clone(value)));
+        values.retain(|value| **value % 2 == 1);
+        assert_eq!(lru.purge_dead(), 2);
+        assert_eq!(lru.len(), 2);
+        assert_eq!(lru.get(&1), Some(Arc::new(1)));
+        assert_eq!(lru.purge_dead(), 0);
+    }
+}
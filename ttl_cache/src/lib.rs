@@ -0,0 +1,5 @@
+pub mod clock;
+pub mod ttl;
+
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use ttl::TtlCache;
@@ -0,0 +1,465 @@
+use crate::clock::{Clock, SystemClock};
+use common::{
+    builder::{BuildError, CacheConfig, FromConfig},
+    Cache, CacheEvent,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    mem,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant},
+};
+
+/// Instant an element expires at, ordered from the earliest with `Never` last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Deadline {
+    At(Instant),
+    /// The time to live reaches beyond what `Instant` can represent, e.g. `Duration::MAX`.
+    Never,
+}
+
+impl Deadline {
+    fn after(now: Instant, ttl: Duration) -> Self {
+        now.checked_add(ttl).map_or(Deadline::Never, Deadline::At)
+    }
+
+    fn has_passed(self, now: Instant) -> bool {
+        match self {
+            Deadline::At(at) => at <= now,
+            Deadline::Never => false,
+        }
+    }
+}
+
+struct Entry<V> {
+    value: V,
+    expires_at: Deadline,
+    /// Position of the entry among those expiring at the same instant.
+    seq: u64,
+}
+
+/// Cache whose elements expire a fixed time after they are inserted, without any replacement
+/// policy: accesses never extend the life of an element.
+///
+/// Deadlines are indexed in a `BTreeMap`, so expired elements are purged from the oldest one
+/// without scanning the others. This stands in for a timer wheel: inserts take O(log n) instead
+/// of O(1). A time to live too long to compute a deadline, such as `Duration::MAX`, never
+/// expires. Purging happens on every insert and can be done explicitly by `purge_expired`; until
+/// then an expired element still counts in `len` but is never returned.
+///
+/// An optional maximum number of elements guards against unbounded growth; when it is reached,
+/// the element expiring first is evicted.
+pub struct TtlCache<K, V, C = SystemClock> {
+    map: HashMap<K, Entry<V>>,
+    expiry: BTreeMap<(Deadline, u64), K>,
+    ttl: Duration,
+    max_entries: Option<usize>,
+    clock: C,
+    seq: u64,
+    listeners: Vec<Sender<CacheEvent<K>>>,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Create a cache whose elements expire `ttl` after they are inserted.
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_clock(ttl, SystemClock)
+    }
+}
+
+impl<K, V, C> TtlCache<K, V, C>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    C: Clock,
+{
+    /// Create a cache reading the time from `clock`.
+    pub fn with_clock(ttl: Duration, clock: C) -> Self {
+        Self {
+            map: HashMap::new(),
+            expiry: BTreeMap::new(),
+            ttl,
+            max_entries: None,
+            clock,
+            seq: 0,
+            listeners: Vec::new(),
+        }
+    }
+
+    /// Time to live of elements inserted by `insert`.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn max_entries(&self) -> Option<usize> {
+        self.max_entries
+    }
+
+    /// Limit the number of elements, or remove the limit with `None`.
+    /// If the cache holds more than `max_entries`, elements expiring first are evicted at once.
+    pub fn set_max_entries(&mut self, max_entries: Option<usize>) {
+        self.max_entries = max_entries;
+        if let Some(max_entries) = max_entries {
+            self.evict_down_to(max_entries);
+        }
+    }
+
+    /// Insert a new key-value pair expiring `ttl` from now instead of the default time to live.
+    pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) {
+        self.purge_expired();
+        self.remove_entry(&key);
+        if let Some(max_entries) = self.max_entries {
+            if max_entries == 0 {
+                return;
+            }
+            self.evict_down_to(max_entries - 1);
+        }
+        let expires_at = Deadline::after(self.clock.now(), ttl);
+        self.seq += 1;
+        self.expiry.insert((expires_at, self.seq), key.clone());
+        self.emit(CacheEvent::Inserted(&key));
+        self.map.insert(
+            key,
+            Entry {
+                value,
+                expires_at,
+                seq: self.seq,
+            },
+        );
+    }
+
+    /// Time left until the element of `key` expires, or `None` if it is absent or has expired.
+    pub fn time_to_live(&self, key: &K) -> Option<Duration> {
+        let left = match self.map.get(key)?.expires_at {
+            Deadline::At(at) => at.checked_duration_since(self.clock.now())?,
+            Deadline::Never => Duration::MAX,
+        };
+        Some(left).filter(|left| !left.is_zero())
+    }
+
+    /// Receiver of every later change of the contents, including expirations found by purging
+    /// or lookups. Dropping the receiver unsubscribes.
+    pub fn subscribe_events(&mut self) -> Receiver<CacheEvent<K>> {
+        let (sender, receiver) = mpsc::channel();
+        self.listeners.push(sender);
+        receiver
+    }
+
+    /// Remove every expired element and return how many were removed.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let mut purged = 0;
+        while self.pop_expired(now).is_some() {
+            purged += 1;
+        }
+        purged
+    }
+
+    /// Remove expired elements and yield them, earliest deadline first, e.g. to persist them
+    /// instead of letting them vanish.
+    /// Elements expired when this is called are yielded; those left when the iterator is dropped
+    /// early stay in the cache until purged. Inserts and lookups drop expired elements without
+    /// yielding them, so drain before those to see every element.
+    pub fn drain_expired(&mut self) -> impl Iterator<Item = (K, V)> + '_ {
+        let now = self.clock.now();
+        std::iter::from_fn(move || self.pop_expired(now))
+    }
+
+    /// Remove the element with the earliest deadline if it is not later than `now`.
+    fn pop_expired(&mut self, now: Instant) -> Option<(K, V)> {
+        let entry = self.expiry.first_entry()?;
+        if !entry.key().0.has_passed(now) {
+            return None;
+        }
+        let key = entry.remove();
+        let entry = self
+            .map
+            .remove(&key)
+            .expect("Every deadline has its element");
+        self.emit(CacheEvent::Expired(&key));
+        Some((key, entry.value))
+    }
+
+    /// Evict elements expiring first until at most `len` are left.
+    fn evict_down_to(&mut self, len: usize) {
+        while self.map.len() > len {
+            let (_, key) = self
+                .expiry
+                .pop_first()
+                .expect("Every element has a deadline");
+            self.map.remove(&key);
+            self.emit(CacheEvent::Evicted(&key));
+        }
+    }
+
+    /// Report `event` to subscribers, forgetting those which are gone.
+    fn emit(&mut self, event: CacheEvent<&K>) {
+        self.listeners
+            .retain(|sender| sender.send(event.map(K::clone)).is_ok());
+    }
+
+    fn remove_entry(&mut self, key: &K) -> Option<Entry<V>> {
+        let entry = self.map.remove(key)?;
+        self.expiry.remove(&(entry.expires_at, entry.seq));
+        Some(entry)
+    }
+
+    /// Remove the entry of `key` if it has expired, and return the live one otherwise.
+    fn live_entry(&mut self, key: &K) -> Option<&Entry<V>> {
+        let expires_at = self.map.get(key)?.expires_at;
+        if expires_at.has_passed(self.clock.now()) {
+            self.remove_entry(key);
+            self.emit(CacheEvent::Expired(key));
+            return None;
+        }
+        self.map.get(key)
+    }
+}
+
+/// Elements expire after `ttl`, which is required, and at most `capacity` are held.
+impl<K, V> FromConfig for TtlCache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn validate(config: &CacheConfig) -> Result<(), BuildError> {
+        match config.ttl {
+            Some(_) => Ok(()),
+            None => Err(BuildError::MissingTtl),
+        }
+    }
+
+    fn from_config(config: CacheConfig) -> Self {
+        let mut cache = Self::new(config.ttl.expect("`validate` requires a ttl"));
+        cache.set_max_entries(Some(config.capacity));
+        cache
+    }
+}
+
+impl<K, V, C> Cache<K, V> for TtlCache<K, V, C>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    C: Clock,
+{
+    /// Insert a new key-value pair expiring after the default time to live.
+    /// Inserting an existing key replaces its value and restarts its time to live.
+    fn insert(&mut self, key: K, value: V) {
+        self.insert_with_ttl(key, value, self.ttl);
+    }
+
+    /// Get a clone of the value of `key` unless it has expired.
+    /// Unlike LRU caches, this does not change which element is evicted next.
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.live_entry(key).map(|entry| entry.value.clone())
+    }
+
+    /// Get the value of `key` if it has not expired, leaving an expired one to be purged later.
+    fn get_quiet(&self, key: &K) -> Option<V> {
+        self.map
+            .get(key)
+            .filter(|entry| !entry.expires_at.has_passed(self.clock.now()))
+            .map(|entry| entry.value.clone())
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.live_entry(key)?;
+        let entry = self.remove_entry(key)?;
+        self.emit(CacheEvent::Removed(key));
+        Some(entry.value)
+    }
+
+    fn clear(&mut self) {
+        let map = mem::take(&mut self.map);
+        map.keys()
+            .for_each(|key| self.emit(CacheEvent::Removed(key)));
+        self.expiry.clear();
+    }
+
+    /// Number of elements, including expired ones which have not been purged yet.
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+    use common::CacheBuilder;
+
+    /// Elements living long enough that only the maximum number evicts them.
+    fn capped(max_entries: usize) -> TtlCache<i32, i32> {
+        let mut cache = TtlCache::new(Duration::from_secs(3600));
+        cache.set_max_entries(Some(max_entries));
+        cache
+    }
+
+    // Evicting the element expiring first is FIFO rather than LRU, so `eviction_order` does not
+    // apply.
+    common::cache_conformance_tests!(
+        capped;
+        update_semantics,
+        capacity_bounds,
+        get_miss,
+        remove,
+        clear,
+        get_quiet
+    );
+
+    fn setup_cache() -> (TtlCache<i32, i32, ManualClock>, ManualClock) {
+        let clock = ManualClock::new();
+        let mut cache = TtlCache::with_clock(Duration::from_secs(10), clock.clone());
+        cache.insert(1, 10);
+        clock.advance(Duration::from_secs(5));
+        cache.insert(2, 20);
+        (cache, clock)
+    }
+
+    #[test]
+    fn expire_after_ttl() {
+        let (mut cache, clock) = setup_cache();
+        assert_eq!(cache.get(&1), Some(10));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), Some(20));
+        assert_eq!(cache.time_to_live(&2), Some(Duration::from_secs(5)));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.remove(&2), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn build_from_builder() {
+        let cache = CacheBuilder::new()
+            .capacity(2)
+            .ttl(Duration::from_secs(10))
+            .build::<TtlCache<i32, i32>>()
+            .unwrap();
+        assert_eq!(cache.ttl(), Duration::from_secs(10));
+        assert_eq!(cache.max_entries(), Some(2));
+        assert_eq!(
+            CacheBuilder::new()
+                .capacity(2)
+                .build::<TtlCache<i32, i32>>()
+                .err(),
+            Some(BuildError::MissingTtl)
+        );
+    }
+
+    #[test]
+    fn unrepresentable_deadline_never_expires() {
+        let clock = ManualClock::new();
+        let mut cache = TtlCache::with_clock(Duration::MAX, clock.clone());
+        cache.insert(1, 10);
+        cache.insert_with_ttl(2, 20, Duration::from_secs(1));
+        clock.advance(Duration::from_secs(3600 * 24 * 365));
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.get(&1), Some(10));
+        assert_eq!(cache.time_to_live(&1), Some(Duration::MAX));
+        cache.set_max_entries(Some(0));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn report_expirations() {
+        let (mut cache, clock) = setup_cache();
+        let events = cache.subscribe_events();
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.get(&1), None);
+        cache.insert(3, 30);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.purge_expired(), 1);
+        cache.remove(&3);
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            vec![
+                CacheEvent::Expired(1),
+                CacheEvent::Inserted(3),
+                CacheEvent::Expired(2),
+                CacheEvent::Removed(3),
+            ]
+        );
+        drop(events);
+        cache.insert(4, 40);
+        assert!(cache.listeners.is_empty());
+    }
+
+    #[test]
+    fn quiet_lookup_leaves_expired_element() {
+        let (cache, clock) = setup_cache();
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.get_quiet(&1), None);
+        assert_eq!(cache.get_quiet(&2), Some(20));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn access_does_not_extend_life() {
+        let (mut cache, clock) = setup_cache();
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(cache.get(&1), Some(10));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn update_restarts_ttl() {
+        let (mut cache, clock) = setup_cache();
+        cache.insert(1, 11);
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(cache.get(&1), Some(11));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn purge_on_insert() {
+        let (mut cache, clock) = setup_cache();
+        cache.insert_with_ttl(3, 30, Duration::from_secs(1));
+        clock.advance(Duration::from_secs(7));
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.purge_expired(), 2);
+        assert_eq!(cache.len(), 1);
+        clock.advance(Duration::from_secs(3));
+        cache.insert(4, 40);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.time_to_live(&2), None);
+    }
+
+    #[test]
+    fn drain_expired_elements() {
+        let (mut cache, clock) = setup_cache();
+        cache.insert_with_ttl(3, 30, Duration::from_secs(1));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(
+            cache.drain_expired().collect::<Vec<_>>(),
+            vec![(3, 30), (1, 10)]
+        );
+        assert_eq!(cache.drain_expired().next(), None);
+        cache.insert(4, 40);
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(
+            cache.drain_expired().take(1).collect::<Vec<_>>(),
+            vec![(2, 20)]
+        );
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.purge_expired(), 1);
+    }
+
+    #[test]
+    fn evict_first_to_expire_over_max_entries() {
+        let (mut cache, _) = setup_cache();
+        cache.insert_with_ttl(3, 30, Duration::from_secs(1));
+        cache.set_max_entries(Some(2));
+        assert_eq!(cache.get(&3), None);
+        cache.insert(4, 40);
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), Some(20));
+        assert_eq!(cache.get(&4), Some(40));
+    }
+}